@@ -0,0 +1,115 @@
+//! Encodes and decodes Oats in a loop, counting heap allocations along the way.
+//!
+//! Run with `cargo bench --bench alloc`. Fails if any of the allocation-free paths allocates.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::{self, Write};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use oats::oat::Oat;
+
+/// Forwards to the system allocator, counting every allocation.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const ITERATIONS: u64 = 1_000_000;
+
+/// A fixed-size `fmt::Write` target living on the stack.
+struct StackBuffer {
+    bytes: [u8; Oat::ENCODED_LEN],
+    len: usize,
+}
+
+impl Write for StackBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.bytes
+            .get_mut(self.len..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Runs `f` for every iteration, printing the time per call and returning the allocation count.
+fn bench(name: &str, mut f: impl FnMut(Oat)) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+
+    for i in 0..ITERATIONS {
+        f(Oat::of(
+            (i % 256) as u8,
+            (i % 4096) as u16,
+            1_671_800_400_000 + i,
+        ));
+    }
+
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    println!(
+        "{name:<12} {:>6.1} ns/iter {allocations:>8} allocations",
+        elapsed.as_nanos() as f64 / ITERATIONS as f64
+    );
+    allocations
+}
+
+fn main() {
+    let encoded: Vec<String> = (0..256)
+        .map(|node| Oat::of(node as u8, 3, 1_671_800_400_000).to_string())
+        .collect();
+
+    let zero_allocation = [
+        bench("encode_to", |oat| {
+            black_box(oat.encode_to(&mut [0; Oat::ENCODED_LEN]));
+        }),
+        bench("write_to", |oat| {
+            let mut buffer = StackBuffer {
+                bytes: [0; Oat::ENCODED_LEN],
+                len: 0,
+            };
+            oat.write_to(&mut buffer).unwrap();
+            black_box(buffer.bytes);
+        }),
+        bench("from_ascii", |oat| {
+            let string = &encoded[oat.node() as usize];
+            black_box(Oat::from_ascii(string.as_bytes()).unwrap());
+        }),
+        bench("from_string", |oat| {
+            let string = &encoded[oat.node() as usize];
+            black_box(Oat::from_string(string).unwrap());
+        }),
+    ];
+
+    // For comparison, allocates the returned `String`.
+    bench("to_string", |oat| {
+        black_box(oat.to_string());
+    });
+
+    assert!(
+        zero_allocation.iter().all(|&allocations| allocations == 0),
+        "An allocation-free path allocated."
+    );
+}
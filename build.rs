@@ -0,0 +1,149 @@
+use std::collections::HashSet;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// The checked-in region table that `src/region.rs` is generated from.
+const REGIONS_TSV: &str = "data/regions.tsv";
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={REGIONS_TSV}");
+
+    let table = fs::read_to_string(REGIONS_TSV).expect("Failed to read region table.");
+    let rows = parse_regions(&table);
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("regions.rs");
+    fs::write(out, render_regions(&rows)).expect("Failed to write generated regions.");
+}
+
+/// A single row of the region table.
+struct Row<'a> {
+    discriminant: u8,
+    prefix: &'a str,
+    iso: Vec<&'a str>,
+    alias: Option<&'a str>,
+    name: &'a str,
+    notes: Option<&'a str>,
+}
+
+/// Parses and validates the region table, panicking with the offending line on error.
+fn parse_regions(table: &str) -> Vec<Row<'_>> {
+    let mut rows: Vec<Row> = Vec::new();
+    let mut prefixes = HashSet::new();
+
+    for (index, line) in table.lines().enumerate() {
+        let line_no = index + 1;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let columns: Vec<&str> = line.split('\t').collect();
+        assert!(
+            columns.len() == 5 || columns.len() == 6,
+            "{REGIONS_TSV}:{line_no}: expected 5 or 6 tab separated columns"
+        );
+
+        let discriminant: u8 = columns[0]
+            .parse()
+            .unwrap_or_else(|_| panic!("{REGIONS_TSV}:{line_no}: invalid discriminant"));
+        assert_eq!(
+            discriminant as usize,
+            rows.len(),
+            "{REGIONS_TSV}:{line_no}: discriminants must be contiguous and in order"
+        );
+
+        let prefix = columns[1];
+        assert!(
+            (1..=3).contains(&prefix.len()) && prefix.bytes().all(|b| b.is_ascii_uppercase()),
+            "{REGIONS_TSV}:{line_no}: prefix must be 1-3 upper-case letters"
+        );
+        assert!(
+            prefixes.insert(prefix),
+            "{REGIONS_TSV}:{line_no}: duplicate prefix {prefix}"
+        );
+
+        let iso: Vec<&str> = columns[2].split(',').collect();
+        assert!(
+            iso.iter()
+                .all(|code| code.len() == 2 && code.bytes().all(|b| b.is_ascii_uppercase())),
+            "{REGIONS_TSV}:{line_no}: ISO codes must be upper-case alpha-2 codes"
+        );
+
+        let alias = match columns[3] {
+            "-" => None,
+            alias => Some(alias),
+        };
+
+        let notes = columns.get(5).copied().filter(|notes| !notes.is_empty());
+
+        rows.push(Row {
+            discriminant,
+            prefix,
+            iso,
+            alias,
+            name: columns[4],
+            notes,
+        });
+    }
+
+    for row in &rows {
+        if let Some(alias) = row.alias {
+            let target = rows
+                .iter()
+                .find(|other| other.prefix == alias)
+                .unwrap_or_else(|| panic!("{REGIONS_TSV}: {} aliases unknown {alias}", row.prefix));
+            assert!(
+                target.alias.is_none(),
+                "{REGIONS_TSV}: {} aliases {alias}, which is an alias itself",
+                row.prefix
+            );
+        }
+    }
+
+    rows
+}
+
+/// Renders the table as an invocation of the `regions!` macro in `src/region.rs`.
+fn render_regions(rows: &[Row]) -> String {
+    let mut out = String::from("regions! {\n");
+
+    for row in rows {
+        writeln!(out, "    #[doc = {:?}]", row.name).unwrap();
+        if let Some(notes) = row.notes {
+            writeln!(out, "    #[doc = \"\"]\n    #[doc = {notes:?}]").unwrap();
+        }
+        if let Some(alias) = row.alias {
+            writeln!(
+                out,
+                "    #[doc = \"\"]\n    #[doc = \"Alias of [`Region::{alias}`].\"]"
+            )
+            .unwrap();
+        }
+
+        let iso = row
+            .iso
+            .iter()
+            .map(|code| format!("{code:?}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let alias = row.alias.map_or_else(
+            || "None".to_string(),
+            |alias| format!("Some(Region::{alias})"),
+        );
+        let notes = row
+            .notes
+            .map_or_else(|| "None".to_string(), |notes| format!("Some({notes:?})"));
+
+        writeln!(
+            out,
+            "    {} = {} {{ iso: [{iso}], alias: {alias}, name: {:?}, notes: {notes} }}",
+            row.prefix, row.discriminant, row.name
+        )
+        .unwrap();
+    }
+
+    out.push_str("}\n");
+    out
+}
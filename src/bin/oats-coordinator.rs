@@ -0,0 +1,41 @@
+//! Coordinator process leasing node ids to Oat generators across hosts.
+//!
+//! Usage: `oats-coordinator <address> <first-node> <last-node>`
+
+use std::env;
+use std::process::ExitCode;
+
+use oats::coordinator::Coordinator;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [address, first, last] = args.as_slice() else {
+        eprintln!("usage: oats-coordinator <address> <first-node> <last-node>");
+        return ExitCode::FAILURE;
+    };
+
+    let (Ok(first), Ok(last)) = (first.parse::<u8>(), last.parse::<u8>()) else {
+        eprintln!("error: nodes must be between 0 and 255");
+        return ExitCode::FAILURE;
+    };
+
+    let coordinator = match Coordinator::bind(address.as_str(), first..=last) {
+        Ok(coordinator) => coordinator,
+        Err(err) => {
+            eprintln!("error: failed to listen on {address}: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Ok(address) = coordinator.local_addr() {
+        println!("leasing nodes {first} to {last} on {address}");
+    }
+
+    match coordinator.serve() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use std::net::SocketAddr;
+use std::path::Path;
+use std::{fmt, fs};
+
+use oats::bowl::GenerationBehavior;
+use oats::epoch::Epoch;
+use serde::Deserialize;
+
+/// The configuration of the server, read from a TOML file.
+///
+/// ```toml
+/// address = "127.0.0.1:8080"
+/// node = 1
+/// behavior = "normal"
+/// epoch = 1672531200000
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The address to listen on.
+    #[serde(default = "default_address")]
+    pub address: SocketAddr,
+    /// The node id of the bowl.
+    pub node: u8,
+    /// The generation behavior of the bowl.
+    #[serde(default)]
+    pub behavior: Behavior,
+    /// The epoch as milliseconds since the Unix epoch, defaults to the Unix epoch itself.
+    pub epoch: Option<u64>,
+    /// The maximum number of ids a single `/ids` request may ask for.
+    #[serde(default = "default_max_count")]
+    pub max_count: usize,
+    /// The number of worker threads handling requests.
+    #[serde(default = "default_workers")]
+    pub workers: usize,
+}
+
+/// Mirrors `GenerationBehavior` for deserialization.
+#[derive(Debug, Default, Copy, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Behavior {
+    Lazy,
+    #[default]
+    Normal,
+    Realtime,
+}
+
+impl From<Behavior> for GenerationBehavior {
+    fn from(behavior: Behavior) -> Self {
+        match behavior {
+            Behavior::Lazy => GenerationBehavior::Lazy,
+            Behavior::Normal => GenerationBehavior::Normal,
+            Behavior::Realtime => GenerationBehavior::Realtime,
+        }
+    }
+}
+
+impl Config {
+    /// Reads the configuration from the given file.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(ConfigError::Io)?;
+        let config: Config = toml::from_str(&contents).map_err(ConfigError::Toml)?;
+
+        if config.workers == 0 {
+            return Err(ConfigError::Invalid("workers must be at least 1"));
+        }
+        if config.max_count == 0 {
+            return Err(ConfigError::Invalid("max_count must be at least 1"));
+        }
+
+        Ok(config)
+    }
+
+    /// Returns the configured epoch.
+    pub fn epoch(&self) -> Epoch {
+        self.epoch.map_or(Epoch::UNIX, Epoch::from_unix_millis)
+    }
+}
+
+/// The reasons the configuration file can not be loaded.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file could not be read.
+    Io(std::io::Error),
+    /// The file is not valid TOML or does not match the expected fields.
+    Toml(toml::de::Error),
+    /// A field holds a value the server can not use.
+    Invalid(&'static str),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "failed to read config: {err}"),
+            ConfigError::Toml(err) => write!(f, "invalid config: {err}"),
+            ConfigError::Invalid(reason) => write!(f, "invalid config: {reason}"),
+        }
+    }
+}
+
+fn default_address() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 8080))
+}
+
+fn default_max_count() -> usize {
+    1000
+}
+
+fn default_workers() -> usize {
+    4
+}
@@ -0,0 +1,228 @@
+//! HTTP server handing out Oats from a single `WrappedBowl`.
+//!
+//! Usage: `oats-server <config.toml>`
+//!
+//! * `GET /id` returns `{"id": "..."}`
+//! * `GET /ids?count=N` returns `{"ids": ["...", ...]}`
+//! * `GET /decode/{id}` returns the node, sequence number and timestamp of the id
+//! * `GET /metrics` returns the statistics of the bowl in the Prometheus text format
+
+mod config;
+
+use std::env;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use oats::bowl::{BowlConfig, GenerateError, WrappedBowl};
+use oats::metrics::{self, BowlRegistry};
+use oats::oat::Oat;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::config::Config;
+
+/// The state shared by all worker threads.
+struct Service {
+    bowl: WrappedBowl,
+    metrics: BowlRegistry,
+    epoch: SystemTime,
+    max_count: usize,
+}
+
+fn main() -> ExitCode {
+    let Some(path) = env::args_os().nth(1).map(PathBuf::from) else {
+        eprintln!("usage: oats-server <config.toml>");
+        return ExitCode::FAILURE;
+    };
+
+    let config = match Config::load(&path) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let bowl_config = BowlConfig::of(config.node as u16)
+        .with_mode(config.behavior.into())
+        .with_epoch(config.epoch());
+    let bowl = match WrappedBowl::from_config(bowl_config) {
+        Ok(bowl) => bowl,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let server = match Server::http(config.address) {
+        Ok(server) => Arc::new(server),
+        Err(err) => {
+            eprintln!("error: failed to listen on {}: {err}", config.address);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let metrics = BowlRegistry::new();
+    metrics.register(&bowl);
+
+    let service = Arc::new(Service {
+        bowl,
+        metrics,
+        epoch: config.epoch().to_system_time(),
+        max_count: config.max_count,
+    });
+
+    // Wake up every worker once on SIGINT/SIGTERM, letting in-flight requests finish.
+    let stopping = Arc::new(AtomicBool::new(false));
+    {
+        let server = Arc::clone(&server);
+        let stopping = Arc::clone(&stopping);
+        let workers = config.workers;
+
+        let installed = ctrlc::set_handler(move || {
+            stopping.store(true, Ordering::SeqCst);
+            for _ in 0..workers {
+                server.unblock();
+            }
+        });
+        if let Err(err) = installed {
+            eprintln!("error: failed to install signal handler: {err}");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    match server.server_addr().to_ip() {
+        Some(address) => println!("listening on {address}"),
+        None => println!("listening on {}", server.server_addr()),
+    }
+
+    let workers: Vec<_> = (0..config.workers)
+        .map(|_| {
+            let server = Arc::clone(&server);
+            let service = Arc::clone(&service);
+            let stopping = Arc::clone(&stopping);
+
+            thread::spawn(move || loop {
+                match server.recv() {
+                    Ok(request) => service.handle(request),
+                    Err(_) if stopping.load(Ordering::SeqCst) => break,
+                    Err(err) => eprintln!("error: failed to receive request: {err}"),
+                }
+            })
+        })
+        .collect();
+
+    workers
+        .into_iter()
+        .for_each(|worker| worker.join().unwrap());
+    println!("shut down");
+
+    ExitCode::SUCCESS
+}
+
+impl Service {
+    fn handle(&self, request: Request) {
+        if *request.method() == Method::Get && request.url() == "/metrics" {
+            return self.metrics(request);
+        }
+
+        let (status, body) = if *request.method() == Method::Get {
+            self.route(request.url())
+        } else {
+            (405, json!({ "error": "method not allowed" }))
+        };
+
+        let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(content_type);
+
+        if let Err(err) = request.respond(response) {
+            eprintln!("error: failed to respond: {err}");
+        }
+    }
+
+    /// Answers a scrape with the statistics of the bowl, in plain text rather than JSON.
+    fn metrics(&self, request: Request) {
+        let content_type = Header::from_bytes("Content-Type", metrics::CONTENT_TYPE).unwrap();
+        let response = Response::from_string(self.metrics.render()).with_header(content_type);
+
+        if let Err(err) = request.respond(response) {
+            eprintln!("error: failed to respond: {err}");
+        }
+    }
+
+    fn route(&self, url: &str) -> (u16, Value) {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+
+        match path {
+            "/id" => match self.bowl.try_generate() {
+                Ok(oat) => (200, json!({ "id": oat.to_string() })),
+                Err(err) => unavailable(err),
+            },
+            "/ids" => match parse_count(query, self.max_count) {
+                Ok(count) => {
+                    let ids: Result<Vec<String>, GenerateError> = (0..count)
+                        .map(|_| self.bowl.try_generate().map(|oat| oat.to_string()))
+                        .collect();
+                    match ids {
+                        Ok(ids) => (200, json!({ "ids": ids })),
+                        Err(err) => unavailable(err),
+                    }
+                }
+                Err(err) => (400, json!({ "error": err })),
+            },
+            _ => match path.strip_prefix("/decode/") {
+                Some(id) => self.decode(id),
+                None => (404, json!({ "error": "not found" })),
+            },
+        }
+    }
+
+    fn decode(&self, id: &str) -> (u16, Value) {
+        if id.len() != 13 || !id.is_ascii() {
+            return (400, json!({ "error": "invalid id" }));
+        }
+
+        match Oat::from_string(id) {
+            Ok(oat) => {
+                let time = self.epoch + Duration::from_millis(oat.timestamp());
+                (
+                    200,
+                    json!({
+                        "id": id,
+                        "node": oat.node(),
+                        "seq": oat.seq(),
+                        "timestamp": oat.timestamp(),
+                        "time": humantime::format_rfc3339_millis(time).to_string(),
+                    }),
+                )
+            }
+            Err(err) => (400, json!({ "error": err.to_string() })),
+        }
+    }
+}
+
+/// Answers a request the bowl can no longer serve.
+fn unavailable(err: GenerateError) -> (u16, Value) {
+    (503, json!({ "error": err.to_string() }))
+}
+
+/// Reads the `count` query parameter, defaulting to a single id.
+fn parse_count(query: &str, max_count: usize) -> Result<usize, String> {
+    let count = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == "count")
+        .map(|(_, value)| value);
+
+    match count.map(str::parse::<usize>) {
+        None => Ok(1),
+        Some(Ok(count)) if (1..=max_count).contains(&count) => Ok(count),
+        Some(_) => Err(format!("count must be between 1 and {max_count}")),
+    }
+}
@@ -0,0 +1,221 @@
+//! Command-line tool for generating, decoding and inspecting Oats.
+
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use data_encoding::{BASE32_NOPAD, HEXLOWER, HEXLOWER_PERMISSIVE};
+use oats::bowl::{BowlConfig, WrappedBowl};
+use oats::epoch::Epoch;
+use oats::layout::{Layout, TimeUnit};
+use oats::oat::Oat;
+use oats::region::Region;
+use serde_json::json;
+
+#[derive(Debug, Parser)]
+#[command(name = "oats", version, about = "Generate, decode and inspect Oats.")]
+struct Cli {
+    /// The epoch the timestamps are relative to, as milliseconds since the Unix epoch or RFC 3339.
+    #[arg(long, global = true, value_parser = parse_epoch)]
+    epoch: Option<SystemTime>,
+
+    /// The unit the timestamps are counted in.
+    #[arg(long, global = true, value_enum, default_value_t = Unit::Ms)]
+    unit: Unit,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Generates new Oats for the given node.
+    Generate {
+        /// The node id to generate Oats for.
+        #[arg(long)]
+        node: u8,
+        /// The number of Oats to generate.
+        #[arg(long, default_value_t = 1)]
+        count: usize,
+        /// The output format of the generated Oats.
+        #[arg(long, value_enum, default_value_t = Format::B64)]
+        format: Format,
+    },
+    /// Decodes a single Oat in any of the supported formats.
+    Decode {
+        /// The Oat to decode.
+        id: String,
+    },
+    /// Decodes Oats read from stdin, one per line, and prints them as JSON lines.
+    Inspect,
+}
+
+/// The formats an Oat can be printed in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// The canonical string representation (node hex followed by base64).
+    B64,
+    /// The 9 bytes as unpadded base32.
+    Base32,
+    /// The 9 bytes as lower-case hex.
+    Hex,
+    /// The raw 9 bytes, without separators.
+    Bytes,
+}
+
+/// The time units an Oat timestamp can be counted in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum Unit {
+    /// Milliseconds.
+    Ms,
+    /// Ticks of 10 milliseconds.
+    #[value(name = "10ms")]
+    TenMs,
+    /// Ticks of 100 milliseconds.
+    #[value(name = "100ms")]
+    HundredMs,
+    /// Seconds.
+    S,
+}
+
+impl From<Unit> for TimeUnit {
+    fn from(unit: Unit) -> Self {
+        match unit {
+            Unit::Ms => TimeUnit::Millis,
+            Unit::TenMs => TimeUnit::TenMillis,
+            Unit::HundredMs => TimeUnit::HundredMillis,
+            Unit::S => TimeUnit::Seconds,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let layout = Layout::STANDARD.with_unit(cli.unit.into());
+
+    let result = match cli.command {
+        Command::Generate {
+            node,
+            count,
+            format,
+        } => generate(node, count, format, cli.epoch, layout),
+        Command::Decode { id } => decode(&id, cli.epoch, &layout),
+        Command::Inspect => inspect(cli.epoch, &layout),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn generate(
+    node: u8,
+    count: usize,
+    format: Format,
+    epoch: Option<SystemTime>,
+    layout: Layout,
+) -> Result<(), String> {
+    let config = BowlConfig::of(node as u16)
+        .with_epoch(epoch.map_or(Epoch::UNIX, Epoch::from_system_time))
+        .with_layout(layout);
+    let bowl = WrappedBowl::from_config(config).map_err(|err| err.to_string())?;
+    let mut stdout = io::stdout().lock();
+
+    for _ in 0..count {
+        let oat = bowl.try_generate().map_err(|err| err.to_string())?;
+        let written = match format {
+            Format::B64 => writeln!(stdout, "{oat}"),
+            Format::Base32 => writeln!(stdout, "{}", BASE32_NOPAD.encode(&oat.to_bytes())),
+            Format::Hex => writeln!(stdout, "{}", HEXLOWER.encode(&oat.to_bytes())),
+            Format::Bytes => stdout.write_all(&oat.to_bytes()),
+        };
+        written.map_err(|err| err.to_string())?;
+    }
+
+    stdout.flush().map_err(|err| err.to_string())
+}
+
+fn decode(id: &str, epoch: Option<SystemTime>, layout: &Layout) -> Result<(), String> {
+    let oat = parse_id(id)?;
+    let region = Region::try_from(oat.node())
+        .map(|region| format!("{} ({})", region.prefix(), region.name()))
+        .unwrap_or_else(|_| "-".to_string());
+
+    println!("node:      {}", oat.node());
+    println!("region:    {region}");
+    println!(
+        "timestamp: {}",
+        format_timestamp(&oat, epoch, layout).unwrap_or_else(|| "-".to_string())
+    );
+    println!("seq:       {}", oat.seq());
+
+    Ok(())
+}
+
+fn inspect(epoch: Option<SystemTime>, layout: &Layout) -> Result<(), String> {
+    let mut stdout = io::stdout().lock();
+
+    for line in io::stdin().lock().lines() {
+        let line = line.map_err(|err| err.to_string())?;
+        let id = line.trim();
+        if id.is_empty() {
+            continue;
+        }
+
+        let value = match parse_id(id) {
+            Ok(oat) => json!({
+                "id": id,
+                "node": oat.node(),
+                "region": Region::try_from(oat.node()).ok().map(Region::prefix),
+                "timestamp": format_timestamp(&oat, epoch, layout),
+                "seq": oat.seq(),
+            }),
+            Err(err) => json!({ "id": id, "error": err }),
+        };
+        writeln!(stdout, "{value}").map_err(|err| err.to_string())?;
+    }
+
+    stdout.flush().map_err(|err| err.to_string())
+}
+
+/// Parses an Oat in any of the textual formats, telling them apart by length.
+fn parse_id(id: &str) -> Result<Oat, String> {
+    if !id.is_ascii() {
+        return Err(format!("{id:?} is not a valid Oat"));
+    }
+
+    let bytes = match id.len() {
+        13 => return Oat::from_string(id).map_err(|err| err.to_string()),
+        15 => BASE32_NOPAD
+            .decode(id.as_bytes())
+            .map_err(|err| err.to_string())?,
+        18 => HEXLOWER_PERMISSIVE
+            .decode(id.as_bytes())
+            .map_err(|err| err.to_string())?,
+        _ => return Err(format!("{id:?} is not a valid Oat")),
+    };
+
+    Oat::from_bytes_ref(&bytes).map_err(|err| err.to_string())
+}
+
+/// Formats the instant of the Oat as RFC 3339, if it lies between the Unix epoch and year 9999.
+fn format_timestamp(oat: &Oat, epoch: Option<SystemTime>, layout: &Layout) -> Option<String> {
+    use std::fmt::Write;
+
+    let mut formatted = String::new();
+    let instant = humantime::format_rfc3339_millis(oat.instant_in(layout, epoch)?);
+    write!(formatted, "{instant}").ok()?;
+    Some(formatted)
+}
+
+fn parse_epoch(value: &str) -> Result<SystemTime, String> {
+    match value.parse::<u64>() {
+        Ok(millis) => Ok(SystemTime::UNIX_EPOCH + Duration::from_millis(millis)),
+        Err(_) => humantime::parse_rfc3339(value).map_err(|err| err.to_string()),
+    }
+}
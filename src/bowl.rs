@@ -1,5 +1,21 @@
+use crate::coordinator::{AllocatorError, Lease, NodeAllocator};
+use crate::epoch::{Epoch, EpochError};
+use crate::layout::{Layout, TimeUnit};
 use crate::oat::Oat;
-use std::{hint::spin_loop, sync::{Arc, Mutex}, time::SystemTime};
+use crate::oat64::Oat64;
+use crate::observer::{BowlCounters, BowlObserver, BowlStats};
+use crate::rng::Rng;
+#[cfg(feature = "uuid")]
+use std::hash::{BuildHasher, RandomState};
+use std::{fmt, time::Duration};
+use std::{
+    hint::spin_loop,
+    sync::{Arc, Mutex},
+    thread::{sleep, yield_now},
+    time::{Instant, SystemTime},
+};
+#[cfg(feature = "uuid")]
+use uuid::Uuid;
 
 /// Defines the behavior of generating new Oats
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
@@ -12,13 +28,262 @@ pub enum GenerationBehavior {
     Realtime,
 }
 
+/// Defines what a bowl does once the sequence numbers of the current tick run out.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SequenceOverflowPolicy {
+    /// Busy-waits for the next tick, sleeping through most of coarse ticks. The default in `Normal` and `Realtime` mode.
+    Spin,
+    /// Sleeps until the next tick, yielding to other threads once it is due.
+    Sleep,
+    /// Moves on to the next tick ahead of the clock, as long as the bowl stays at most the given
+    /// duration ahead, and waits like `Spin` beyond. `Lazy` mode borrows without limit by default.
+    Borrow(Duration),
+    /// Fails with `GenerateError::SequenceExhausted` until the next tick.
+    Fail,
+}
+
+impl GenerationBehavior {
+    /// Returns the overflow policy bowls in this mode use unless configured otherwise.
+    pub const fn default_overflow_policy(self) -> SequenceOverflowPolicy {
+        match self {
+            GenerationBehavior::Lazy => SequenceOverflowPolicy::Borrow(Duration::MAX),
+            GenerationBehavior::Normal | GenerationBehavior::Realtime => {
+                SequenceOverflowPolicy::Spin
+            }
+        }
+    }
+}
+
 /// The WrappedBowl is a thread-safe wrapper around the Bowl.
 #[derive(Debug, Clone)]
-pub struct WrappedBowl(Arc<Mutex<Bowl>>);
+pub struct WrappedBowl {
+    bowl: Arc<Mutex<Bowl>>,
+    counters: Arc<BowlCounters>,
+}
+
+/// Describes a bowl to be created with `WrappedBowl::from_config`.
+///
+/// Unlike the plain constructors, the configuration is checked before the bowl is created, so a
+/// bowl counting from an epoch in the future or from the current time, or one that runs out of
+/// timestamps within `min_horizon`, is never created.
+///
+/// # Examples
+///
+/// ```
+/// use oats::bowl::{BowlConfig, GenerationBehavior, WrappedBowl};
+/// use oats::epoch::Epoch;
+///
+/// let config = BowlConfig::of(1)
+///     .with_mode(GenerationBehavior::Realtime)
+///     .with_epoch(Epoch::OATS);
+/// let wrapped_bowl = WrappedBowl::from_config(config).unwrap();
+///
+/// assert_eq!(wrapped_bowl.generate().node(), 1);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BowlConfig {
+    node: u16,
+    mode: GenerationBehavior,
+    epoch: Epoch,
+    layout: Layout,
+    min_horizon: Duration,
+    overflow_policy: Option<SequenceOverflowPolicy>,
+}
+
+/// The reasons a bowl can not be created from a `BowlConfig`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BowlError {
+    /// The node id does not fit into the layout.
+    InvalidNode(u16),
+    /// The epoch lies in the future or is the current time.
+    InvalidEpoch(EpochError),
+    /// The last timestamp the layout can hold, counted from the epoch, lies too close to the current time.
+    HorizonTooClose(SystemTime),
+}
+
+impl BowlConfig {
+    /// The default time a new bowl must be able to generate Oats for, one year.
+    pub const DEFAULT_MIN_HORIZON: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+    /// Creates a configuration for the given node id, in `Normal` mode, counting from the Unix epoch with the standard layout.
+    pub const fn of(node: u16) -> Self {
+        BowlConfig {
+            node,
+            mode: GenerationBehavior::Normal,
+            epoch: Epoch::UNIX,
+            layout: Layout::STANDARD,
+            min_horizon: BowlConfig::DEFAULT_MIN_HORIZON,
+            overflow_policy: None,
+        }
+    }
+
+    /// Returns a copy of the configuration using the given generation behavior mode.
+    pub const fn with_mode(self, mode: GenerationBehavior) -> Self {
+        BowlConfig { mode, ..self }
+    }
+
+    /// Returns a copy of the configuration counting from the given epoch.
+    pub const fn with_epoch(self, epoch: Epoch) -> Self {
+        BowlConfig { epoch, ..self }
+    }
+
+    /// Returns a copy of the configuration generating Oats with the given layout.
+    pub const fn with_layout(self, layout: Layout) -> Self {
+        BowlConfig { layout, ..self }
+    }
+
+    /// Returns a copy of the configuration requiring the horizon to lie at least `min_horizon` ahead.
+    pub const fn with_min_horizon(self, min_horizon: Duration) -> Self {
+        BowlConfig {
+            min_horizon,
+            ..self
+        }
+    }
+
+    /// Returns a copy of the configuration using the given policy once the sequence numbers run out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::bowl::{BowlConfig, GenerationBehavior, SequenceOverflowPolicy, WrappedBowl};
+    /// use oats::epoch::Epoch;
+    /// use std::time::Duration;
+    ///
+    /// // Run at most 50 milliseconds ahead of the clock, then wait.
+    /// let config = BowlConfig::of(1)
+    ///     .with_mode(GenerationBehavior::Lazy)
+    ///     .with_epoch(Epoch::OATS)
+    ///     .with_overflow_policy(SequenceOverflowPolicy::Borrow(Duration::from_millis(50)));
+    /// let wrapped_bowl = WrappedBowl::from_config(config).unwrap();
+    ///
+    /// for _ in 0..10_000 {
+    ///     wrapped_bowl.generate();
+    /// }
+    /// assert!(wrapped_bowl.drift() <= Duration::from_millis(51));
+    /// ```
+    pub const fn with_overflow_policy(self, overflow_policy: SequenceOverflowPolicy) -> Self {
+        BowlConfig {
+            overflow_policy: Some(overflow_policy),
+            ..self
+        }
+    }
+
+    /// Returns the node id.
+    pub const fn node(&self) -> u16 {
+        self.node
+    }
+
+    /// Returns the generation behavior mode.
+    pub const fn mode(&self) -> GenerationBehavior {
+        self.mode
+    }
+
+    /// Returns the epoch.
+    pub const fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    /// Returns the layout.
+    pub const fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Returns the overflow policy, the default of the mode unless configured otherwise.
+    pub const fn overflow_policy(&self) -> SequenceOverflowPolicy {
+        match self.overflow_policy {
+            Some(overflow_policy) => overflow_policy,
+            None => self.mode.default_overflow_policy(),
+        }
+    }
+
+    /// Returns the time a new bowl must be able to generate Oats for.
+    pub const fn min_horizon(&self) -> Duration {
+        self.min_horizon
+    }
+
+    /// Returns the last instant a bowl with this configuration can generate Oats for.
+    ///
+    /// # Returns
+    ///
+    /// The horizon, or `None` if it lies beyond what `SystemTime` can represent, as it does for
+    /// layouts with 64 timestamp bits counting in seconds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::bowl::BowlConfig;
+    /// use oats::epoch::Epoch;
+    /// use oats::layout::Layout;
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// // 41 bits of milliseconds last for almost 70 years.
+    /// let config = BowlConfig::of(1).with_epoch(Epoch::OATS).with_layout(Layout::JS_SAFE);
+    /// let horizon = Epoch::OATS.to_system_time() + Duration::from_millis((1 << 41) - 1);
+    ///
+    /// assert_eq!(config.horizon(), Some(horizon));
+    /// ```
+    pub fn horizon(&self) -> Option<SystemTime> {
+        horizon(Some(self.epoch.to_system_time()), &self.layout)
+    }
+}
 
 impl WrappedBowl {
+    /// Creates a new WrappedBowl instance from a checked configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The node id, generation behavior mode, epoch and layout of the bowl.
+    ///
+    /// # Returns
+    ///
+    /// A new WrappedBowl instance, or a `BowlError` if the node does not fit into the layout, the
+    /// epoch is not fit to count from, or the horizon lies less than `min_horizon` ahead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::bowl::{BowlConfig, BowlError, WrappedBowl};
+    /// use oats::epoch::{Epoch, EpochError};
+    /// use std::time::SystemTime;
+    ///
+    /// let now = Epoch::from_system_time(SystemTime::now());
+    /// let result = WrappedBowl::from_config(BowlConfig::of(1).with_epoch(now));
+    ///
+    /// assert_eq!(result.err(), Some(BowlError::InvalidEpoch(EpochError::TooRecent(now))));
+    /// ```
+    pub fn from_config(config: BowlConfig) -> Result<Self, BowlError> {
+        if config.node > config.layout.max_node() {
+            return Err(BowlError::InvalidNode(config.node));
+        }
+        config.epoch.validate().map_err(BowlError::InvalidEpoch)?;
+
+        // A horizon beyond `SystemTime` is far enough away.
+        if let Some(horizon) = horizon(Some(config.epoch.to_system_time()), &config.layout) {
+            let remaining = horizon
+                .duration_since(SystemTime::now())
+                .unwrap_or_default();
+            if remaining < config.min_horizon {
+                return Err(BowlError::HorizonTooClose(horizon));
+            }
+        }
+
+        let mut bowl = Bowl::with_layout(
+            config.node,
+            config.mode,
+            Some(config.epoch.to_system_time()),
+            config.layout,
+        );
+        bowl.overflow_policy = config.overflow_policy();
+
+        Ok(WrappedBowl::wrap(bowl))
+    }
+
     /// Creates a new WrappedBowl instance with the given node id, generation behavior mode and optional epoch.
     ///
+    /// An epoch at the current time makes the timestamps meaningless across restarts. It is
+    /// accepted for compatibility and reported as a `tracing` warning if the `tracing` feature is
+    /// enabled; use `WrappedBowl::from_config` to reject it as an `EpochError` instead.
+    ///
     /// # Arguments
     ///
     /// * `node` - The node id for the WrappedBowl instance.
@@ -29,17 +294,189 @@ impl WrappedBowl {
     ///
     /// A new WrappedBowl instance.
     ///
+    /// # Panics
+    ///
+    /// Panics if the epoch lies in the future, which `WrappedBowl::from_config` reports as
+    /// `EpochError::InFuture`.
+    ///
     /// # Examples
     ///
     /// ```
     /// use oats::bowl::{GenerationBehavior, WrappedBowl};
-    /// use std::time::SystemTime;
+    /// use oats::epoch::Epoch;
     ///
-    /// let wrapped_bowl = WrappedBowl::of(1, GenerationBehavior::Normal, Some(SystemTime::now()));
+    /// let epoch = Epoch::OATS.to_system_time();
+    /// let wrapped_bowl = WrappedBowl::of(1, GenerationBehavior::Normal, Some(epoch));
     /// ```
-
     pub fn of(node: u8, mode: GenerationBehavior, epoch: Option<SystemTime>) -> Self {
-        WrappedBowl(Arc::new(Mutex::new(Bowl::of(node, mode, epoch))))
+        check_legacy_epoch(epoch);
+        WrappedBowl::wrap(Bowl::of(node, mode, epoch))
+    }
+
+    /// Creates a new WrappedBowl instance generating Oats with the given layout.
+    ///
+    /// Like `WrappedBowl::of`, it warns about an epoch at the current time and panics if the
+    /// epoch lies in the future.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node id for the WrappedBowl instance, which must fit into the layout.
+    /// * `mode` - The generation behavior mode for the WrappedBowl instance.
+    /// * `epoch` - An optional epoch for the WrappedBowl instance.
+    /// * `layout` - The bit layout of the generated Oats.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::bowl::{GenerationBehavior, WrappedBowl};
+    /// use oats::layout::{Layout, TimeUnit};
+    ///
+    /// let layout = Layout::new(10, 42, 14, TimeUnit::Millis);
+    /// let wrapped_bowl = WrappedBowl::with_layout(1000, GenerationBehavior::Normal, None, layout);
+    ///
+    /// assert_eq!(wrapped_bowl.generate().node_in(&layout), 1000);
+    /// ```
+    pub fn with_layout(
+        node: u16,
+        mode: GenerationBehavior,
+        epoch: Option<SystemTime>,
+        layout: Layout,
+    ) -> Self {
+        check_legacy_epoch(epoch);
+        WrappedBowl::wrap(Bowl::with_layout(node, mode, epoch, layout))
+    }
+
+    /// Shares the bowl and its counters between all clones.
+    fn wrap(bowl: Bowl) -> Self {
+        WrappedBowl {
+            counters: bowl.counters.clone(),
+            bowl: Arc::new(Mutex::new(bowl)),
+        }
+    }
+
+    /// Returns the layout of the Oats generated by this bowl.
+    pub fn layout(&self) -> Layout {
+        self.bowl.lock().expect("Failed to get lock.").layout
+    }
+
+    /// Returns the node id of the Oats generated by this bowl.
+    pub fn node(&self) -> u16 {
+        self.bowl.lock().expect("Failed to get lock.").node
+    }
+
+    /// Returns the epoch the timestamps of this bowl count from, the Unix epoch if none was given.
+    ///
+    /// # Returns
+    ///
+    /// The epoch, or `None` if it lies before the Unix epoch and can not be an `Epoch`.
+    pub fn epoch(&self) -> Option<Epoch> {
+        epoch_of(self.bowl.lock().expect("Failed to get lock.").epoch)
+    }
+
+    /// Returns whether both handles share the same bowl.
+    pub fn same_bowl(&self, other: &WrappedBowl) -> bool {
+        Arc::ptr_eq(&self.bowl, &other.bowl)
+    }
+
+    /// Returns the bowl, starting the sequence numbers of every new tick at a random offset drawn from `rng`.
+    ///
+    /// Consecutive Oats then no longer reveal how many Oats the node generated within a tick.
+    /// The offset is picked from the lower half of the sequence numbers, so at least half of them
+    /// remain available per tick. Within the node the Oats stay unique and strictly increasing.
+    ///
+    /// The setting applies to all clones of the bowl, so it is best chosen right after creation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::bowl::{BowlConfig, WrappedBowl};
+    /// use oats::epoch::Epoch;
+    /// use oats::rng::SplitMix64;
+    ///
+    /// let wrapped_bowl = WrappedBowl::from_config(BowlConfig::of(1).with_epoch(Epoch::OATS))
+    ///     .unwrap()
+    ///     .with_random_seq(SplitMix64::from_entropy());
+    ///
+    /// let first = wrapped_bowl.generate();
+    /// let second = wrapped_bowl.generate();
+    ///
+    /// assert!(first < second);
+    /// ```
+    pub fn with_random_seq<R: Rng + Send + 'static>(self, rng: R) -> Self {
+        {
+            let mut lock = self.bowl.lock().expect("Failed to get lock.");
+            lock.random_seq = Some(RandomSeq(Box::new(rng)));
+            lock.current_seq = lock.first_seq();
+        }
+
+        self
+    }
+
+    /// Returns the bowl, reporting its events to `observer` in addition to the built-in counters.
+    ///
+    /// A bowl has at most one observer, setting another one replaces it. Like `with_random_seq`,
+    /// the setting applies to all clones of the bowl.
+    pub fn with_observer<O: BowlObserver + 'static>(self, observer: O) -> Self {
+        self.bowl.lock().expect("Failed to get lock.").observer =
+            Some(Observer(Arc::new(observer)));
+
+        self
+    }
+
+    /// Returns the counters of the bowl, shared by all its clones.
+    ///
+    /// The counters are kept with atomics, so reading them never waits for the bowl.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::bowl::{BowlConfig, WrappedBowl};
+    /// use oats::epoch::Epoch;
+    ///
+    /// let wrapped_bowl = WrappedBowl::from_config(BowlConfig::of(1).with_epoch(Epoch::OATS)).unwrap();
+    /// for _ in 0..10 {
+    ///     wrapped_bowl.generate();
+    /// }
+    ///
+    /// assert_eq!(wrapped_bowl.stats().generated(), 10);
+    /// ```
+    pub fn stats(&self) -> BowlStats {
+        self.counters.snapshot()
+    }
+
+    /// Returns how far the timestamp of the most recent Oat lies ahead of the clock.
+    ///
+    /// Only bowls borrowing time from the future, like those in `Lazy` mode, run ahead of the
+    /// clock; all others report zero.
+    pub fn drift(&self) -> Duration {
+        self.bowl.lock().expect("Failed to get lock.").drift()
+    }
+
+    /// Returns the node, epoch, counters and drift of the bowl, read under a single lock.
+    ///
+    /// Generation counts Oats while holding the lock, so `generated` matches the other fields.
+    #[cfg(feature = "prometheus")]
+    pub(crate) fn sample(&self) -> (u16, Option<Epoch>, BowlStats, Duration) {
+        let lock = self.bowl.lock().expect("Failed to get lock.");
+        (
+            lock.node,
+            epoch_of(lock.epoch),
+            self.counters.snapshot(),
+            lock.drift(),
+        )
+    }
+
+    /// Returns the last instant this bowl can generate Oats for.
+    ///
+    /// Past the horizon the timestamp no longer fits into the layout, and `try_generate` fails
+    /// with `GenerateError::Exhausted`.
+    ///
+    /// # Returns
+    ///
+    /// The horizon, or `None` if it lies beyond what `SystemTime` can represent.
+    pub fn horizon(&self) -> Option<SystemTime> {
+        let lock = self.bowl.lock().expect("Failed to get lock.");
+        horizon(lock.epoch, &lock.layout)
     }
 
     /// Generates a new Oat value based on given parameters.
@@ -48,114 +485,663 @@ impl WrappedBowl {
     ///
     /// A new Oat value.
     ///
+    /// # Panics
+    ///
+    /// Panics once the bowl has passed its horizon, or if the sequence numbers ran out under
+    /// `SequenceOverflowPolicy::Fail`, see `try_generate`.
+    ///
     /// # Examples
     ///
     /// ```
     /// use oats::bowl::{GenerationBehavior, WrappedBowl};
-    /// use std::time::SystemTime;
+    /// use oats::epoch::Epoch;
     ///
-    /// let wrapped_bowl = WrappedBowl::of(1, GenerationBehavior::Normal, Some(SystemTime::now()));
+    /// let epoch = Epoch::OATS.to_system_time();
+    /// let wrapped_bowl = WrappedBowl::of(1, GenerationBehavior::Normal, Some(epoch));
     /// let oat = wrapped_bowl.generate();
     ///
     /// assert_eq!(oat.node(), 1);
     /// ```
     pub fn generate(&self) -> Oat {
+        self.try_generate().expect("Failed to generate Oat.")
+    }
+
+    /// Generates a new Oat value, unless the timestamp no longer fits into the layout.
+    ///
+    /// # Returns
+    ///
+    /// A new Oat value, or `GenerateError::Exhausted` once the bowl has passed its horizon, or
+    /// `GenerateError::SequenceExhausted` if the sequence numbers ran out under
+    /// `SequenceOverflowPolicy::Fail`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::bowl::{GenerationBehavior, WrappedBowl};
+    /// use oats::layout::{Layout, TimeUnit};
+    ///
+    /// // 40 bits of seconds since the Unix epoch last until the year 36812.
+    /// let layout = Layout::new(8, 40, 12, TimeUnit::Seconds);
+    /// let wrapped_bowl = WrappedBowl::with_layout(1, GenerationBehavior::Normal, None, layout);
+    ///
+    /// assert!(wrapped_bowl.try_generate().is_ok());
+    /// ```
+    pub fn try_generate(&self) -> Result<Oat, GenerateError> {
+        let node;
+        let seq;
+        let time;
+        let oat;
+        let observer;
+
+        // The span covers the whole call, so `generated` is reported within it as well.
+        #[cfg(feature = "tracing")]
+        let span = tracing::trace_span!("oats.generate", node = tracing::field::Empty).entered();
+
+        {
+            let mut lock = self.bowl.lock().expect("Failed to get lock.");
+            #[cfg(feature = "tracing")]
+            span.record("node", lock.node);
+
+            node = lock.node;
+            seq = lock.new_seq()?;
+            time = lock.last_timestamp;
+            observer = lock.observer.clone();
+
+            if time > lock.layout.max_timestamp() {
+                return Err(GenerateError::Exhausted);
+            }
+            // Counted under the lock, so `sample` reads it together with the other fields.
+            oat = Oat::with_layout(&lock.layout, node, seq, time);
+            self.counters.generated(&oat);
+
+            drop(lock)
+        }
+
+        self.notify_generated(&oat, observer);
+
+        Ok(oat)
+    }
+
+    /// Generates a new Oat64 value, packing the fields into a single `u64`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layout of the bowl needs more than 64 bits, once the bowl has passed its
+    /// horizon, or if the sequence numbers ran out under `SequenceOverflowPolicy::Fail`, see
+    /// `try_generate_oat64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::bowl::{GenerationBehavior, WrappedBowl};
+    /// use oats::epoch::Epoch;
+    /// use oats::layout::Layout;
+    ///
+    /// let epoch = Epoch::OATS.to_system_time();
+    /// let wrapped_bowl = WrappedBowl::with_layout(1, GenerationBehavior::Normal, Some(epoch), Layout::JS_SAFE);
+    /// let oat = wrapped_bowl.generate_oat64();
+    ///
+    /// assert_eq!(oat.node_in(&Layout::JS_SAFE), 1);
+    /// assert!(oat.to_u64() < 1 << 53);
+    /// ```
+    pub fn generate_oat64(&self) -> Oat64 {
+        self.try_generate_oat64()
+            .expect("Failed to generate Oat64.")
+    }
+
+    /// Generates a new Oat64 value, unless the timestamp no longer fits into the layout.
+    ///
+    /// # Returns
+    ///
+    /// A new Oat64 value, or `GenerateError::Exhausted` once the bowl has passed its horizon,
+    /// `GenerateError::SequenceExhausted` if the sequence numbers ran out under
+    /// `SequenceOverflowPolicy::Fail`, or `GenerateError::LayoutTooWide` if the layout of the
+    /// bowl needs more than 64 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::bowl::{GenerateError, GenerationBehavior, WrappedBowl};
+    /// use oats::layout::{Layout, TimeUnit};
+    ///
+    /// // 30 bits of milliseconds since the Unix epoch ended in January 1970.
+    /// let layout = Layout::new(8, 30, 12, TimeUnit::Millis);
+    /// let wrapped_bowl = WrappedBowl::with_layout(1, GenerationBehavior::Normal, None, layout);
+    ///
+    /// assert_eq!(wrapped_bowl.try_generate_oat64(), Err(GenerateError::Exhausted));
+    /// ```
+    pub fn try_generate_oat64(&self) -> Result<Oat64, GenerateError> {
+        let layout;
         let node;
         let seq;
         let time;
+        let generated;
+        let observer;
+
+        #[cfg(feature = "tracing")]
+        let span = tracing::trace_span!("oats.generate", node = tracing::field::Empty).entered();
 
         {
-            let mut lock = self.0.lock().expect("Failed to get lock.");
+            let mut lock = self.bowl.lock().expect("Failed to get lock.");
+            #[cfg(feature = "tracing")]
+            span.record("node", lock.node);
+
+            // Checked before taking a sequence number, which would be lost otherwise.
+            if lock.layout.total_bits() > 64 {
+                return Err(GenerateError::LayoutTooWide(lock.layout.total_bits()));
+            }
+
+            layout = lock.layout;
             node = lock.node;
-            seq = lock.new_seq();
+            seq = lock.new_seq()?;
             time = lock.last_timestamp;
+            observer = lock.observer.clone();
+
+            if time > layout.max_timestamp() {
+                return Err(GenerateError::Exhausted);
+            }
+            // Counted under the lock, so `sample` reads it together with the other fields.
+            generated = Oat::with_layout(&layout, node, seq, time);
+            self.counters.generated(&generated);
 
             drop(lock)
         }
 
-        Oat::of(node, seq, time)
+        let oat = Oat64::with_layout(&layout, node, seq, time);
+        self.notify_generated(&generated, observer);
+
+        Ok(oat)
+    }
+
+    /// Moves the last timestamp ahead, as if the clock had stepped back by `ticks` since.
+    #[cfg(test)]
+    pub(crate) fn step_clock_back(&self, ticks: u64) {
+        self.bowl
+            .lock()
+            .expect("Failed to get lock.")
+            .step_clock_back(ticks);
+    }
+
+    /// Hands the generated Oat to the observer, outside of the lock.
+    ///
+    /// The built-in counters have already counted it while the lock was held.
+    fn notify_generated(&self, oat: &Oat, observer: Option<Observer>) {
+        if let Some(Observer(observer)) = observer {
+            observer.generated(oat);
+        }
+    }
+}
+
+/// The LeasedBowl is a WrappedBowl bound to a lease from a `NodeAllocator`.
+///
+/// It refuses to generate Oats once the lease has expired, as the node may already have been
+/// handed to someone else. Keep it alive by calling `renew` well before the deadline.
+#[derive(Debug, Clone)]
+pub struct LeasedBowl {
+    bowl: WrappedBowl,
+    lease: Arc<Mutex<Lease>>,
+}
+
+/// The reasons generating an Oat can fail.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GenerateError {
+    /// The lease on the node has expired.
+    LeaseExpired,
+    /// The timestamp no longer fits into the layout, the bowl has passed its horizon.
+    Exhausted,
+    /// The sequence numbers of the current tick ran out under `SequenceOverflowPolicy::Fail`.
+    SequenceExhausted,
+    /// The layout needs the given number of bits, more than the 64 of an `Oat64`.
+    LayoutTooWide(u32),
+}
+
+impl LeasedBowl {
+    /// Creates a new LeasedBowl for the leased node with the given generation behavior mode and optional epoch.
+    ///
+    /// # Arguments
+    ///
+    /// * `lease` - The lease on the node id.
+    /// * `mode` - The generation behavior mode for the LeasedBowl instance.
+    /// * `epoch` - An optional epoch for the LeasedBowl instance.
+    pub fn of(lease: Lease, mode: GenerationBehavior, epoch: Option<SystemTime>) -> Self {
+        LeasedBowl {
+            bowl: WrappedBowl::of(lease.node(), mode, epoch),
+            lease: Arc::new(Mutex::new(lease)),
+        }
+    }
+
+    /// Returns the current lease.
+    pub fn lease(&self) -> Lease {
+        *self.lease.lock().expect("Failed to get lock.")
+    }
+
+    /// Renews the lease for `ttl` through the given allocator.
+    pub fn renew<A: NodeAllocator>(
+        &self,
+        allocator: &mut A,
+        ttl: Duration,
+    ) -> Result<(), AllocatorError> {
+        let current = self.lease();
+        let renewed = allocator.renew(&current, ttl)?;
+
+        *self.lease.lock().expect("Failed to get lock.") = renewed;
+        Ok(())
+    }
+
+    /// Generates a new Oat value, unless the lease has expired.
+    ///
+    /// # Returns
+    ///
+    /// A new Oat value, or `GenerateError::LeaseExpired` once the deadline has passed, or
+    /// `GenerateError::Exhausted` once the bowl has passed its horizon.
+    pub fn generate(&self) -> Result<Oat, GenerateError> {
+        let lease = self.lease.lock().expect("Failed to get lock.");
+        if lease.is_expired() {
+            return Err(GenerateError::LeaseExpired);
+        }
+
+        self.bowl.try_generate()
+    }
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::LeaseExpired => write!(f, "the lease on the node has expired"),
+            GenerateError::Exhausted => {
+                write!(f, "the timestamp no longer fits into the layout")
+            }
+            GenerateError::SequenceExhausted => {
+                write!(f, "the sequence numbers of the current tick ran out")
+            }
+            GenerateError::LayoutTooWide(bits) => {
+                write!(f, "layout needs {bits} bits, only 64 fit into an Oat64")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+impl fmt::Display for BowlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BowlError::InvalidNode(node) => write!(f, "node {node} does not fit into the layout"),
+            BowlError::InvalidEpoch(err) => write!(f, "invalid epoch: {err}"),
+            BowlError::HorizonTooClose(horizon) => {
+                let since_unix = horizon
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default();
+                write!(
+                    f,
+                    "the layout runs out of timestamps {} ms after the Unix epoch",
+                    since_unix.as_millis()
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for BowlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BowlError::InvalidEpoch(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// The UuidBowl generates RFC 9562 UUIDv7 values with the clock and sequence counter of a bowl.
+///
+/// The Unix millisecond timestamp fills `unix_ts_ms`, the 12-bit sequence number `rand_a` and the
+/// node byte the top of `rand_b`, followed by 54 random bits. Like a realtime `WrappedBowl`, it
+/// waits for the next millisecond once the sequence numbers run out, so the UUIDs of one node are
+/// strictly increasing, even when the clock steps backwards.
+#[cfg(feature = "uuid")]
+#[derive(Debug, Clone)]
+pub struct UuidBowl {
+    bowl: WrappedBowl,
+    random: RandomState,
+}
+
+#[cfg(feature = "uuid")]
+impl UuidBowl {
+    /// Creates a new UuidBowl for the given node id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::bowl::UuidBowl;
+    ///
+    /// let uuid_bowl = UuidBowl::of(7);
+    /// let first = uuid_bowl.generate();
+    /// let second = uuid_bowl.generate();
+    ///
+    /// assert_eq!(first.get_version_num(), 7);
+    /// assert_eq!(UuidBowl::node_of(&first), Some(7));
+    /// assert!(first < second);
+    /// ```
+    pub fn of(node: u8) -> Self {
+        UuidBowl {
+            bowl: WrappedBowl::of(node, GenerationBehavior::Realtime, None),
+            random: RandomState::new(),
+        }
+    }
+
+    /// Generates a new UUIDv7.
+    pub fn generate(&self) -> Uuid {
+        let oat = self.bowl.generate();
+        // The random bits only need to differ between processes sharing a node over time.
+        let random = self.random.hash_one(oat.to_u128()) as u128 & ((1 << 54) - 1);
+
+        Uuid::from_u128(
+            (oat.timestamp() as u128) << 80
+                | 0x7 << 76
+                | (oat.seq() as u128) << 64
+                | 0b10 << 62
+                | (oat.node() as u128) << 54
+                | random,
+        )
+    }
+
+    /// Returns the node id embedded in a UUIDv7 generated by a UuidBowl.
+    ///
+    /// # Returns
+    ///
+    /// The node id, or `None` if the UUID is not a UUIDv7.
+    pub fn node_of(uuid: &Uuid) -> Option<u8> {
+        if uuid.get_version_num() != 7 {
+            return None;
+        }
+
+        Some((uuid.as_u128() >> 54) as u8)
+    }
+}
+
+/// The random number generator picking the first sequence number of a tick.
+struct RandomSeq(Box<dyn Rng + Send>);
+
+impl fmt::Debug for RandomSeq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RandomSeq")
+    }
+}
+
+/// The observer of a bowl, besides its counters.
+#[derive(Clone)]
+struct Observer(Arc<dyn BowlObserver>);
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Observer")
     }
 }
 
 /// The Bowl is used for generating Oat values in a unified way.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug)]
 pub(crate) struct Bowl {
     mode: GenerationBehavior,
-    node: u8,
+    layout: Layout,
+    node: u16,
     epoch: Option<SystemTime>,
-    current_seq: u16,    // max 12 bits (= 1,5 bytes)
-    last_timestamp: u64, // max 44 bits (= 5,5 bytes)
+    overflow_policy: SequenceOverflowPolicy,
+    random_seq: Option<RandomSeq>,
+    counters: Arc<BowlCounters>,
+    observer: Option<Observer>,
+    current_seq: u16,    // max `layout.seq_bits()` bits
+    last_timestamp: u64, // max `layout.timestamp_bits()` bits
+    last_clock: u64,     // the ticks the clock read for the previous Oat
 }
 
 impl Bowl {
     pub(crate) fn of(node: u8, mode: GenerationBehavior, epoch: Option<SystemTime>) -> Self {
+        Bowl::with_layout(node as u16, mode, epoch, Layout::STANDARD)
+    }
+
+    pub(crate) fn with_layout(
+        node: u16,
+        mode: GenerationBehavior,
+        epoch: Option<SystemTime>,
+        layout: Layout,
+    ) -> Self {
+        assert!(
+            node <= layout.max_node(),
+            "Node {node} does not fit into the layout."
+        );
+
+        let now_ticks = get_time_ticks(epoch, layout.unit());
+
         Bowl {
             mode,
+            layout,
             node,
             epoch,
+            overflow_policy: mode.default_overflow_policy(),
+            random_seq: None,
+            counters: Arc::new(BowlCounters::default()),
+            observer: None,
             current_seq: 0,
-            last_timestamp: get_time_millis(epoch),
+            last_timestamp: now_ticks,
+            last_clock: now_ticks,
         }
     }
 
-    pub(crate) fn new_seq(&mut self) -> u16 {
-        self.current_seq = (self.current_seq + 1) % 4096;
-        let mut now_millis = get_time_millis(self.epoch);
+    pub(crate) fn new_seq(&mut self) -> Result<u16, GenerateError> {
+        let seq_range = 1u32 << self.layout.seq_bits();
+        let previous_seq = self.current_seq;
+        self.current_seq = ((previous_seq as u32 + 1) % seq_range) as u16;
+        let now_ticks = get_time_ticks(self.epoch, self.layout.unit());
+
+        if now_ticks < self.last_clock {
+            let behind = self.layout.unit().to_duration(self.last_clock - now_ticks);
+            self.notify(|observer| observer.clock_regressed(behind));
+        }
+        self.last_clock = now_ticks;
 
         match self.mode {
-            GenerationBehavior::Lazy => {
+            GenerationBehavior::Lazy | GenerationBehavior::Normal => {
+                // Maintenance `last_timestamp` whenever the sequence numbers wrap around.
                 if self.current_seq == 0 {
-                    self.last_timestamp += 1;
-                }
-            }
-            GenerationBehavior::Normal => {
-                // Maintenance `last_time_millis` for every 4096 ids generated.
-                if self.current_seq == 0 {
-                    if now_millis == self.last_timestamp {
-                        now_millis = biding_time_conditions(self.last_timestamp, self.epoch);
-                    }
-
-                    self.last_timestamp = now_millis;
+                    self.wrap(now_ticks, previous_seq)?;
                 }
             }
             GenerationBehavior::Realtime => {
                 // supplement code for 'clock is moving backwards situation'.
 
-                // If the milliseconds of the current clock are equal to
-                // the number of milliseconds of the most recently generated id,
-                // then check if enough 4096 are generated,
-                // if enough then busy wait until the next millisecond.
-                if now_millis == self.last_timestamp {
+                // If the ticks of the current clock are equal to, or after a clock step
+                // behind, the ticks of the most recently generated id,
+                // then check if the sequence numbers wrapped around,
+                // if so then move on to the next tick according to the overflow policy.
+                if now_ticks <= self.last_timestamp {
                     if self.current_seq == 0 {
-                        now_millis = biding_time_conditions(self.last_timestamp, self.epoch);
-                        self.last_timestamp = now_millis;
+                        self.wrap(now_ticks, previous_seq)?;
                     }
                 } else {
-                    self.last_timestamp = now_millis;
-                    self.current_seq = 0;
+                    self.last_timestamp = now_ticks;
+                    self.current_seq = self.first_seq();
                 }
             }
         }
 
-        self.current_seq
+        Ok(self.current_seq)
     }
+
+    /// Moves on to the next tick once the sequence numbers of `last_timestamp` ran out.
+    ///
+    /// The wrap is only reported once the bowl did move on, so attempts failing under
+    /// `SequenceOverflowPolicy::Fail` are not counted; those restore `previous_seq` instead.
+    fn wrap(&mut self, now_ticks: u64, previous_seq: u16) -> Result<(), GenerateError> {
+        let wrapped = self.last_timestamp;
+        self.last_timestamp = self.next_timestamp(now_ticks).inspect_err(|_| {
+            self.current_seq = previous_seq;
+        })?;
+        self.current_seq = self.first_seq();
+        self.notify(|observer| observer.sequence_wrapped(wrapped));
+
+        Ok(())
+    }
+
+    /// Returns the sequence number a new tick starts with, a random one from the lower half if enabled.
+    fn first_seq(&mut self) -> u16 {
+        match &mut self.random_seq {
+            Some(RandomSeq(rng)) => {
+                let half_range = 1u64 << (self.layout.seq_bits() - 1);
+                (rng.next_u64() % half_range) as u16
+            }
+            None => 0,
+        }
+    }
+
+    /// Returns the timestamp to continue with once the sequence numbers of `last_timestamp` ran out.
+    ///
+    /// `Lazy` mode always continues with the following tick, the other modes with the current one.
+    /// If the clock has not moved past `last_timestamp` yet, the overflow policy decides whether
+    /// to wait for it, borrow the following tick or fail.
+    fn next_timestamp(&self, now_ticks: u64) -> Result<u64, GenerateError> {
+        let unit = self.layout.unit();
+        let following = self.last_timestamp + 1;
+
+        let latest_ticks = if now_ticks > self.last_timestamp {
+            now_ticks
+        } else {
+            let started = Instant::now();
+            let latest_ticks = match self.overflow_policy {
+                SequenceOverflowPolicy::Borrow(max_drift)
+                    if unit.to_duration(following - now_ticks) <= max_drift =>
+                {
+                    return Ok(following);
+                }
+                SequenceOverflowPolicy::Spin | SequenceOverflowPolicy::Borrow(_) => {
+                    biding_time_conditions(self.last_timestamp, self.epoch, unit)
+                }
+                SequenceOverflowPolicy::Sleep => {
+                    sleeping_time_conditions(self.last_timestamp, self.epoch, unit)
+                }
+                SequenceOverflowPolicy::Fail => return Err(GenerateError::SequenceExhausted),
+            };
+
+            let waited = started.elapsed();
+            self.notify(|observer| observer.waited(waited));
+            latest_ticks
+        };
+
+        match self.mode {
+            GenerationBehavior::Lazy => Ok(following),
+            GenerationBehavior::Normal | GenerationBehavior::Realtime => Ok(latest_ticks),
+        }
+    }
+
+    /// Reports an event to the counters and the observer.
+    fn notify(&self, event: impl Fn(&dyn BowlObserver)) {
+        event(&*self.counters);
+        if let Some(Observer(observer)) = &self.observer {
+            event(&**observer);
+        }
+    }
+
+    pub(crate) fn drift(&self) -> Duration {
+        // Unlike `get_time_ticks`, never panic: drift is read by metrics scrapes.
+        let since_epoch = SystemTime::now()
+            .duration_since(self.epoch.unwrap_or(SystemTime::UNIX_EPOCH))
+            .unwrap_or_default();
+        let now_ticks = self.layout.unit().from_duration(since_epoch);
+        self.layout
+            .unit()
+            .to_duration(self.last_timestamp.saturating_sub(now_ticks))
+    }
+
+    /// Moves the last timestamp ahead, as if the clock had stepped back by `ticks` since.
+    #[cfg(test)]
+    pub(crate) fn step_clock_back(&mut self, ticks: u64) {
+        self.last_timestamp += ticks;
+    }
+}
+
+/// Checks the epoch of a bowl created without `WrappedBowl::from_config`.
+///
+/// Epochs at the current time are only reported as a `tracing` warning, for compatibility.
+///
+/// # Panics
+///
+/// Panics if the epoch lies in the future, as the bowl could not take a timestamp yet.
+fn check_legacy_epoch(epoch: Option<SystemTime>) {
+    let Some(epoch) = epoch_of(epoch) else {
+        return;
+    };
+
+    match epoch.validate() {
+        Err(err @ EpochError::InFuture(_)) => panic!("Invalid epoch: {err}."),
+        #[cfg(feature = "tracing")]
+        Err(err) => tracing::warn!(
+            %err,
+            "Oats will not be comparable across restarts, use WrappedBowl::from_config to reject the epoch"
+        ),
+        _ => {}
+    }
+}
+
+/// Converts the epoch of a bowl into an `Epoch`, if it lies between the Unix epoch and the largest `Epoch`.
+fn epoch_of(epoch: Option<SystemTime>) -> Option<Epoch> {
+    let since_unix = epoch
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .ok()?;
+    u64::try_from(since_unix.as_millis())
+        .ok()
+        .map(Epoch::from_unix_millis)
+}
+
+/// Returns the instant of the largest timestamp `layout` can hold, counted from `epoch`, if `SystemTime` can represent it.
+fn horizon(epoch: Option<SystemTime>, layout: &Layout) -> Option<SystemTime> {
+    epoch
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+        .checked_add(layout.unit().to_duration(layout.max_timestamp()))
 }
 
-fn get_time_millis(epoch: Option<SystemTime>) -> u64 {
-    SystemTime::now()
-        .duration_since(epoch.unwrap_or(SystemTime::UNIX_EPOCH))
-        .expect("Clock went backwards.")
-        .as_millis() as u64
+fn get_time_ticks(epoch: Option<SystemTime>, unit: TimeUnit) -> u64 {
+    unit.from_duration(
+        SystemTime::now()
+            .duration_since(epoch.unwrap_or(SystemTime::UNIX_EPOCH))
+            .expect("Clock went backwards."),
+    )
 }
 
-// Constantly refreshing the latest milliseconds by busy waiting.
-fn biding_time_conditions(last_time_millis: u64, epoch: Option<SystemTime>) -> u64 {
-    let mut latest_time_millis: u64;
+// Constantly refreshing the latest tick by busy waiting, sleeping through most of coarse ticks.
+fn biding_time_conditions(last_ticks: u64, epoch: Option<SystemTime>, unit: TimeUnit) -> u64 {
+    let next_tick = unit.to_duration(last_ticks + 1);
     loop {
-        latest_time_millis = get_time_millis(epoch);
-        if latest_time_millis > last_time_millis {
-            return latest_time_millis;
+        let since_epoch = SystemTime::now()
+            .duration_since(epoch.unwrap_or(SystemTime::UNIX_EPOCH))
+            .expect("Clock went backwards.");
+        let latest_ticks = unit.from_duration(since_epoch);
+        if latest_ticks > last_ticks {
+            return latest_ticks;
+        }
+
+        match next_tick.checked_sub(since_epoch) {
+            Some(remaining) if unit != TimeUnit::Millis && remaining > unit.tick() / 10 => {
+                sleep(remaining - unit.tick() / 10)
+            }
+            _ => spin_loop(),
+        }
+    }
+}
+
+// Refreshing the latest tick by sleeping until it is due, yielding while the clock catches up.
+fn sleeping_time_conditions(last_ticks: u64, epoch: Option<SystemTime>, unit: TimeUnit) -> u64 {
+    let next_tick = unit.to_duration(last_ticks + 1);
+    loop {
+        let since_epoch = SystemTime::now()
+            .duration_since(epoch.unwrap_or(SystemTime::UNIX_EPOCH))
+            .expect("Clock went backwards.");
+        let latest_ticks = unit.from_duration(since_epoch);
+        if latest_ticks > last_ticks {
+            return latest_ticks;
+        }
+
+        match next_tick.checked_sub(since_epoch) {
+            Some(remaining) if !remaining.is_zero() => sleep(remaining),
+            _ => yield_now(),
         }
-        spin_loop();
     }
 }
@@ -0,0 +1,97 @@
+use crate::oat::Oat;
+use crate::siphash::siphash24;
+use core::fmt;
+
+/// The number of Feistel rounds.
+const ROUNDS: u8 = 8;
+
+/// The number of bits in each half of the 72-bit block.
+const HALF_BITS: u32 = 36;
+
+/// The mask of a 36-bit half.
+const HALF_MASK: u64 = (1 << HALF_BITS) - 1;
+
+/// Maps Oats to opaque Oats of the same length and back, keyed with a 128-bit secret.
+///
+/// The 72 bits of an Oat are run through an 8-round Feistel network over two 36-bit halves,
+/// using SipHash-2-4 under the key as the round function. The result is a permutation of all
+/// 72-bit values, so every Oat maps to exactly one token, which is again an Oat and therefore
+/// encodes to 13 characters. Without the key, the token reveals neither the node nor the
+/// creation time, and tokens do not sort by creation time.
+///
+/// This is obfuscation, not authentication: any 13 characters decrypt to some Oat, so a token
+/// proves nothing about where it came from. Treat decrypted Oats as untrusted input, and keep
+/// the key as secret as any other credential, since it can not be rotated without changing
+/// every token handed out.
+///
+/// # Examples
+///
+/// ```
+/// use oats::cipher::OatCipher;
+/// use oats::oat::Oat;
+///
+/// let cipher = OatCipher::of(*b"0123456789abcdef");
+/// let oat = Oat::of(1, 3, 1671800400_000);
+///
+/// let token = cipher.encrypt(&oat);
+///
+/// assert_ne!(token, oat);
+/// assert_eq!(cipher.decrypt(&token), oat);
+/// ```
+#[derive(Clone, Eq, PartialEq)]
+pub struct OatCipher {
+    key: [u8; 16],
+}
+
+impl OatCipher {
+    /// Creates a cipher with the given 128-bit key.
+    pub const fn of(key: [u8; 16]) -> Self {
+        OatCipher { key }
+    }
+
+    /// Returns the token standing for `oat`.
+    pub fn encrypt(&self, oat: &Oat) -> Oat {
+        let value = oat.to_u128();
+        let mut left = (value >> HALF_BITS) as u64 & HALF_MASK;
+        let mut right = value as u64 & HALF_MASK;
+
+        for round in 0..ROUNDS {
+            (left, right) = (right, left ^ self.round(round, right));
+        }
+
+        from_halves(left, right)
+    }
+
+    /// Returns the Oat a token stands for.
+    pub fn decrypt(&self, token: &Oat) -> Oat {
+        let value = token.to_u128();
+        let mut left = (value >> HALF_BITS) as u64 & HALF_MASK;
+        let mut right = value as u64 & HALF_MASK;
+
+        for round in (0..ROUNDS).rev() {
+            (left, right) = (right ^ self.round(round, left), left);
+        }
+
+        from_halves(left, right)
+    }
+
+    /// The round function, keyed SipHash-2-4 over the round number and a half, cut to 36 bits.
+    fn round(&self, round: u8, half: u64) -> u64 {
+        let half = half.to_le_bytes();
+        let input = [round, half[0], half[1], half[2], half[3], half[4]];
+
+        siphash24(&self.key, &input) & HALF_MASK
+    }
+}
+
+/// Joins two 36-bit halves into an Oat.
+fn from_halves(left: u64, right: u64) -> Oat {
+    Oat::from_u128((left as u128) << HALF_BITS | right as u128).unwrap()
+}
+
+/// Keeps the key out of logs.
+impl fmt::Debug for OatCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OatCipher").finish_non_exhaustive()
+    }
+}
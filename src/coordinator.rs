@@ -0,0 +1,492 @@
+//! Node id assignment across hosts.
+//!
+//! A small coordinator process hands out time limited leases on node ids, similar to the worker
+//! id assignment used with snowflake ids. Clients talk to it through the
+//! [`NodeAllocator`](crate::coordinator::NodeAllocator) trait;
+//! [`TcpAllocator`](crate::coordinator::TcpAllocator) is the reference implementation for the
+//! line based protocol below.
+//!
+//! | Request                     | Success             | Failure          |
+//! |-----------------------------|---------------------|------------------|
+//! | `ACQUIRE <ttl>`             | `OK <node> <token>` | `ERR exhausted`  |
+//! | `RENEW <node> <token> <ttl>`| `OK <node> <token>` | `ERR expired`    |
+//! | `RELEASE <node> <token>`    | `OK`                | `ERR expired`    |
+//!
+//! TTLs are given in milliseconds and may not exceed [`MAX_TTL`](crate::coordinator::MAX_TTL).
+//! Malformed requests are answered with `ERR protocol <reason>`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasher, RandomState};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// The longest TTL the coordinator grants, one day.
+pub const MAX_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A node id leased from a `NodeAllocator` until its deadline.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Lease {
+    node: u8,
+    token: u64,
+    deadline: Instant,
+}
+
+impl Lease {
+    /// Returns the leased node id.
+    pub fn node(&self) -> u8 {
+        self.node
+    }
+
+    /// Returns the token identifying this holder of the lease.
+    pub fn token(&self) -> u64 {
+        self.token
+    }
+
+    /// Returns the instant after which the node must no longer be used.
+    ///
+    /// The deadline is measured from before the request was sent, so it never lies after the
+    /// expiry seen by the coordinator.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Returns whether the lease has expired.
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.deadline
+    }
+}
+
+/// Hands out, renews and releases leases on node ids.
+pub trait NodeAllocator {
+    /// Leases a free node for `ttl`.
+    fn acquire(&mut self, ttl: Duration) -> Result<Lease, AllocatorError>;
+
+    /// Extends a lease that has not expired yet by `ttl` from now.
+    fn renew(&mut self, lease: &Lease, ttl: Duration) -> Result<Lease, AllocatorError>;
+
+    /// Gives the node back before the lease expires.
+    fn release(&mut self, lease: Lease) -> Result<(), AllocatorError>;
+}
+
+/// The reasons a `NodeAllocator` can not hand out, renew or release a lease.
+#[derive(Debug)]
+pub enum AllocatorError {
+    /// Every node is leased by someone else.
+    Exhausted,
+    /// The lease expired or is held by someone else.
+    Expired,
+    /// The peer sent or received something that does not follow the protocol.
+    Protocol(String),
+    /// The connection to the coordinator failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for AllocatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AllocatorError::Exhausted => write!(f, "all nodes are leased"),
+            AllocatorError::Expired => write!(f, "the lease has expired"),
+            AllocatorError::Protocol(reason) => write!(f, "protocol error: {reason}"),
+            AllocatorError::Io(err) => write!(f, "connection to coordinator failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for AllocatorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AllocatorError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// A `NodeAllocator` talking to a `Coordinator` over TCP.
+#[derive(Debug)]
+pub struct TcpAllocator {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl TcpAllocator {
+    /// Connects to the coordinator at the given address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use oats::bowl::{GenerationBehavior, LeasedBowl};
+    /// use oats::coordinator::{Coordinator, NodeAllocator, TcpAllocator};
+    ///
+    /// let coordinator = Coordinator::bind("127.0.0.1:0", 0..=15).unwrap().spawn().unwrap();
+    ///
+    /// let mut allocator = TcpAllocator::connect(coordinator.local_addr()).unwrap();
+    /// let lease = allocator.acquire(Duration::from_secs(30)).unwrap();
+    ///
+    /// let leased_bowl = LeasedBowl::of(lease, GenerationBehavior::Normal, None);
+    /// assert_eq!(leased_bowl.generate().unwrap().node(), 0);
+    /// ```
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let writer = TcpStream::connect(addr)?;
+        writer.set_nodelay(true)?;
+        let reader = BufReader::new(writer.try_clone()?);
+
+        Ok(TcpAllocator { reader, writer })
+    }
+
+    /// Sends a request and returns the fields following `OK`.
+    fn call(&mut self, request: &str) -> Result<Vec<String>, AllocatorError> {
+        writeln!(self.writer, "{request}").map_err(AllocatorError::Io)?;
+
+        let mut line = String::new();
+        if self
+            .reader
+            .read_line(&mut line)
+            .map_err(AllocatorError::Io)?
+            == 0
+        {
+            return Err(AllocatorError::Io(io::ErrorKind::UnexpectedEof.into()));
+        }
+
+        let mut fields = line.split_whitespace().map(str::to_string);
+        match fields.next().as_deref() {
+            Some("OK") => Ok(fields.collect()),
+            Some("ERR") => match fields.next().as_deref() {
+                Some("exhausted") => Err(AllocatorError::Exhausted),
+                Some("expired") => Err(AllocatorError::Expired),
+                _ => Err(AllocatorError::Protocol(line.trim().to_string())),
+            },
+            _ => Err(AllocatorError::Protocol(line.trim().to_string())),
+        }
+    }
+
+    /// Parses an `OK <node> <token>` reply into a lease ending `ttl` after `sent`.
+    fn lease(fields: &[String], sent: Instant, ttl: Duration) -> Result<Lease, AllocatorError> {
+        match fields {
+            [node, token] => Ok(Lease {
+                node: node.parse().map_err(|_| protocol_error("invalid node"))?,
+                token: token.parse().map_err(|_| protocol_error("invalid token"))?,
+                deadline: sent
+                    .checked_add(ttl)
+                    .ok_or_else(|| protocol_error("invalid ttl"))?,
+            }),
+            _ => Err(protocol_error("expected node and token")),
+        }
+    }
+}
+
+impl NodeAllocator for TcpAllocator {
+    fn acquire(&mut self, ttl: Duration) -> Result<Lease, AllocatorError> {
+        let sent = Instant::now();
+        let fields = self.call(&format!("ACQUIRE {}", ttl.as_millis()))?;
+        TcpAllocator::lease(&fields, sent, ttl)
+    }
+
+    fn renew(&mut self, lease: &Lease, ttl: Duration) -> Result<Lease, AllocatorError> {
+        let sent = Instant::now();
+        let fields = self.call(&format!(
+            "RENEW {} {} {}",
+            lease.node,
+            lease.token,
+            ttl.as_millis()
+        ))?;
+        TcpAllocator::lease(&fields, sent, ttl)
+    }
+
+    fn release(&mut self, lease: Lease) -> Result<(), AllocatorError> {
+        self.call(&format!("RELEASE {} {}", lease.node, lease.token))
+            .map(drop)
+    }
+}
+
+/// The coordinator handing out leases on a range of node ids.
+///
+/// Expired nodes are only handed out again after a grace period, covering clock skew between
+/// the coordinator and its clients.
+#[derive(Debug)]
+pub struct Coordinator {
+    listener: TcpListener,
+    state: Arc<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    nodes: RangeInclusive<u8>,
+    grace: Duration,
+    holders: Mutex<HashMap<u8, Holder>>,
+    next_token: AtomicU64,
+    tokens: RandomState,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Holder {
+    token: u64,
+    expires: Instant,
+}
+
+impl Coordinator {
+    /// Binds a coordinator for the given node range to `addr`.
+    pub fn bind(addr: impl ToSocketAddrs, nodes: RangeInclusive<u8>) -> io::Result<Self> {
+        Ok(Coordinator {
+            listener: TcpListener::bind(addr)?,
+            state: Arc::new(State {
+                nodes,
+                grace: Duration::from_secs(1),
+                holders: Mutex::new(HashMap::new()),
+                next_token: AtomicU64::new(0),
+                tokens: RandomState::new(),
+            }),
+        })
+    }
+
+    /// Sets how long an expired node stays unassigned, defaults to one second.
+    pub fn with_grace(mut self, grace: Duration) -> Self {
+        Arc::get_mut(&mut self.state)
+            .expect("Coordinator is not shared before serving.")
+            .grace = grace;
+        self
+    }
+
+    /// Returns the address the coordinator is listening on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serves clients on the current thread until the listener fails.
+    ///
+    /// Errors accepting a single connection, like an aborted handshake or running out of file
+    /// descriptors, are skipped.
+    pub fn serve(self) -> io::Result<()> {
+        self.serve_until(&AtomicBool::new(false))
+    }
+
+    /// Serves clients on a background thread until the returned handle is shut down.
+    ///
+    /// If accepting connections fails, the thread stops serving and `CoordinatorHandle::shutdown`
+    /// returns the error.
+    pub fn spawn(self) -> io::Result<CoordinatorHandle> {
+        let addr = self.local_addr()?;
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || self.serve_until(&stop))
+        };
+
+        Ok(CoordinatorHandle {
+            addr,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    fn serve_until(self, stop: &AtomicBool) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            if stop.load(Ordering::SeqCst) {
+                break;
+            }
+
+            let stream = match stream {
+                Ok(stream) => stream,
+                // A failed connection must not take down the leases of all others.
+                Err(err) if is_transient(&err) => {
+                    thread::sleep(Duration::from_millis(10));
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            let state = Arc::clone(&self.state);
+            thread::spawn(move || {
+                let _ = state.handle_connection(stream);
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl State {
+    fn handle_connection(&self, stream: TcpStream) -> io::Result<()> {
+        let mut writer = stream.try_clone()?;
+
+        for line in BufReader::new(stream).lines() {
+            let response = self.respond(&line?);
+            writeln!(writer, "{response}")?;
+        }
+
+        Ok(())
+    }
+
+    fn respond(&self, request: &str) -> String {
+        let fields: Vec<&str> = request.split_whitespace().collect();
+
+        let result = match fields.as_slice() {
+            ["ACQUIRE", ttl] => parse_ttl(ttl).and_then(|ttl| self.acquire(ttl)),
+            ["RENEW", node, token, ttl] => parse_holder(node, token).and_then(|(node, token)| {
+                parse_ttl(ttl).and_then(|ttl| self.renew(node, token, ttl))
+            }),
+            ["RELEASE", node, token] => {
+                parse_holder(node, token).and_then(|(node, token)| self.release(node, token))
+            }
+            _ => Err("protocol unknown request".to_string()),
+        };
+
+        match result {
+            Ok(reply) => reply,
+            Err(reason) => format!("ERR {reason}"),
+        }
+    }
+
+    fn acquire(&self, ttl: Duration) -> Result<String, String> {
+        let now = Instant::now();
+        let expires = expiry(now, ttl)?;
+        let mut holders = self.holders.lock().expect("Failed to get lock.");
+
+        let node = self
+            .nodes
+            .clone()
+            .find(|node| {
+                holders.get(node).is_none_or(|holder| {
+                    holder
+                        .expires
+                        .checked_add(self.grace)
+                        .is_some_and(|free| free <= now)
+                })
+            })
+            .ok_or("exhausted")?;
+
+        // Hash a counter under a random key, so clients can not guess the tokens of others.
+        let token = self
+            .tokens
+            .hash_one(self.next_token.fetch_add(1, Ordering::SeqCst));
+        holders.insert(node, Holder { token, expires });
+
+        Ok(format!("OK {node} {token}"))
+    }
+
+    fn renew(&self, node: u8, token: u64, ttl: Duration) -> Result<String, String> {
+        let now = Instant::now();
+        let expires = expiry(now, ttl)?;
+        let mut holders = self.holders.lock().expect("Failed to get lock.");
+
+        match holders.get_mut(&node) {
+            Some(holder) if holder.token == token && holder.expires > now => {
+                holder.expires = expires;
+                Ok(format!("OK {node} {token}"))
+            }
+            _ => Err("expired".to_string()),
+        }
+    }
+
+    fn release(&self, node: u8, token: u64) -> Result<String, String> {
+        let mut holders = self.holders.lock().expect("Failed to get lock.");
+
+        match holders.get(&node) {
+            Some(holder) if holder.token == token => {
+                holders.remove(&node);
+                Ok("OK".to_string())
+            }
+            _ => Err("expired".to_string()),
+        }
+    }
+}
+
+/// A coordinator serving on a background thread, shut down when dropped.
+#[derive(Debug)]
+pub struct CoordinatorHandle {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl CoordinatorHandle {
+    /// Returns the address the coordinator is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Stops accepting new connections and waits for the serving thread to finish.
+    ///
+    /// # Returns
+    ///
+    /// The error that made the coordinator stop serving early, if any.
+    pub fn shutdown(mut self) -> io::Result<()> {
+        self.stop_serving()
+    }
+
+    fn stop_serving(&mut self) -> io::Result<()> {
+        let Some(thread) = self.thread.take() else {
+            return Ok(());
+        };
+
+        self.stop.store(true, Ordering::SeqCst);
+        // Wake up the blocking accept call so it notices the stop flag.
+        let _ = TcpStream::connect(self.addr);
+        thread
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::other("coordinator thread panicked")))
+    }
+}
+
+impl Drop for CoordinatorHandle {
+    fn drop(&mut self) {
+        let _ = self.stop_serving();
+    }
+}
+
+fn parse_ttl(ttl: &str) -> Result<Duration, String> {
+    match ttl.parse() {
+        Ok(millis) if millis > 0 && Duration::from_millis(millis) <= MAX_TTL => {
+            Ok(Duration::from_millis(millis))
+        }
+        _ => Err("protocol invalid ttl".to_string()),
+    }
+}
+
+/// Returns the instant a lease of `ttl` taken at `now` expires, rejecting TTLs beyond what
+/// `Instant` can represent.
+fn expiry(now: Instant, ttl: Duration) -> Result<Instant, String> {
+    now.checked_add(ttl)
+        .ok_or_else(|| "protocol invalid ttl".to_string())
+}
+
+/// Returns whether accepting a connection failed for that connection alone, or for a shortage
+/// that passes, rather than because the listener itself broke.
+pub(crate) fn is_transient(err: &io::Error) -> bool {
+    // Running out of file descriptors has no stable `ErrorKind`.
+    #[cfg(unix)]
+    const OUT_OF_FILES: [i32; 2] = [23, 24];
+    #[cfg(windows)]
+    const OUT_OF_FILES: [i32; 1] = [10024];
+    #[cfg(not(any(unix, windows)))]
+    const OUT_OF_FILES: [i32; 0] = [];
+
+    matches!(
+        err.kind(),
+        io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::OutOfMemory
+    ) || err
+        .raw_os_error()
+        .is_some_and(|code| OUT_OF_FILES.contains(&code))
+}
+
+fn parse_holder(node: &str, token: &str) -> Result<(u8, u64), String> {
+    let node = node.parse().map_err(|_| "protocol invalid node")?;
+    let token = token.parse().map_err(|_| "protocol invalid token")?;
+    Ok((node, token))
+}
+
+fn protocol_error(reason: &str) -> AllocatorError {
+    AllocatorError::Protocol(reason.to_string())
+}
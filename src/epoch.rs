@@ -0,0 +1,200 @@
+use core::fmt;
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime};
+
+/// The instant the timestamps of a bowl count from, in milliseconds since the Unix epoch.
+///
+/// An epoch has to stay the same for as long as its Oats are in use. Deriving it from the current
+/// time, as in `SystemTime::now()`, changes the meaning of every timestamp on restart and lets a
+/// restarted bowl hand out ids that were handed out before.
+///
+/// # Examples
+///
+/// ```
+/// use oats::epoch::Epoch;
+///
+/// const LAUNCH: Epoch = Epoch::from_unix_millis(1704067200_000);
+///
+/// assert!(Epoch::UNIX < Epoch::OATS);
+/// assert!(Epoch::OATS < LAUNCH);
+/// ```
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct Epoch {
+    unix_millis: u64,
+}
+
+/// The reasons an epoch is rejected.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum EpochError {
+    /// The epoch lies in the future, so the bowl can not count time from it yet.
+    InFuture(Epoch),
+    /// The epoch is less than `Epoch::MIN_AGE` old, which usually means it was taken from `SystemTime::now()`.
+    TooRecent(Epoch),
+    /// The registry already holds a different epoch under the id.
+    AlreadyRegistered(u8),
+}
+
+impl Epoch {
+    /// The Unix epoch, 1970-01-01T00:00:00Z.
+    pub const UNIX: Epoch = Epoch::from_unix_millis(0);
+
+    /// The custom epoch of this crate, 2023-01-01T00:00:00Z.
+    pub const OATS: Epoch = Epoch::from_unix_millis(1_672_531_200_000);
+
+    /// Epochs younger than this are treated as taken from the current time.
+    #[cfg(feature = "std")]
+    pub const MIN_AGE: Duration = Duration::from_secs(1);
+
+    /// Creates an epoch from the number of milliseconds since the Unix epoch.
+    pub const fn from_unix_millis(unix_millis: u64) -> Self {
+        Epoch { unix_millis }
+    }
+
+    /// Returns the number of milliseconds since the Unix epoch.
+    pub const fn unix_millis(&self) -> u64 {
+        self.unix_millis
+    }
+
+    /// Creates an epoch from a `SystemTime`, truncated to milliseconds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the time lies before the Unix epoch.
+    #[cfg(feature = "std")]
+    pub fn from_system_time(time: SystemTime) -> Self {
+        let since_unix = time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("Epoch lies before the Unix epoch.");
+        Epoch::from_unix_millis(since_unix.as_millis() as u64)
+    }
+
+    /// Returns the epoch as a `SystemTime`.
+    #[cfg(feature = "std")]
+    pub fn to_system_time(&self) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_millis(self.unix_millis)
+    }
+
+    /// Checks that the epoch is at least `Epoch::MIN_AGE` old.
+    ///
+    /// # Returns
+    ///
+    /// `EpochError::InFuture` or `EpochError::TooRecent` for epochs that are not fit to count from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::SystemTime;
+    /// use oats::epoch::{Epoch, EpochError};
+    ///
+    /// assert_eq!(Epoch::OATS.validate(), Ok(()));
+    ///
+    /// let now = Epoch::from_system_time(SystemTime::now());
+    /// assert_eq!(now.validate(), Err(EpochError::TooRecent(now)));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn validate(&self) -> Result<(), EpochError> {
+        match SystemTime::now().duration_since(self.to_system_time()) {
+            Err(_) => Err(EpochError::InFuture(*self)),
+            Ok(age) if age < Epoch::MIN_AGE => Err(EpochError::TooRecent(*self)),
+            Ok(_) => Ok(()),
+        }
+    }
+}
+
+impl Default for Epoch {
+    fn default() -> Self {
+        Epoch::UNIX
+    }
+}
+
+/// Maps the small epoch ids carried by the wire format to epochs.
+///
+/// # Examples
+///
+/// ```
+/// use oats::epoch::{Epoch, EpochRegistry};
+///
+/// const REGISTRY: EpochRegistry = EpochRegistry::STANDARD
+///     .with(2, Epoch::from_unix_millis(1704067200_000));
+///
+/// assert_eq!(REGISTRY.get(1), Some(Epoch::OATS));
+/// assert_eq!(REGISTRY.id_of(Epoch::from_unix_millis(1704067200_000)), Some(2));
+/// assert_eq!(REGISTRY.get(3), None);
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EpochRegistry {
+    epochs: [Option<Epoch>; 256],
+}
+
+impl EpochRegistry {
+    /// The registry of the epochs defined by this crate: `0` for `Epoch::UNIX`, `1` for `Epoch::OATS`.
+    pub const STANDARD: EpochRegistry = EpochRegistry::new()
+        .with(0, Epoch::UNIX)
+        .with(1, Epoch::OATS);
+
+    /// Creates an empty registry.
+    pub const fn new() -> Self {
+        EpochRegistry {
+            epochs: [None; 256],
+        }
+    }
+
+    /// Returns a copy of the registry with `epoch` registered under `id`, replacing any previous entry.
+    pub const fn with(mut self, id: u8, epoch: Epoch) -> Self {
+        self.epochs[id as usize] = Some(epoch);
+        self
+    }
+
+    /// Registers `epoch` under `id`.
+    ///
+    /// # Returns
+    ///
+    /// `EpochError::AlreadyRegistered` if a different epoch is registered under `id`.
+    pub fn register(&mut self, id: u8, epoch: Epoch) -> Result<(), EpochError> {
+        match self.epochs[id as usize] {
+            Some(registered) if registered != epoch => Err(EpochError::AlreadyRegistered(id)),
+            _ => {
+                self.epochs[id as usize] = Some(epoch);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the epoch registered under `id`.
+    pub const fn get(&self, id: u8) -> Option<Epoch> {
+        self.epochs[id as usize]
+    }
+
+    /// Returns the smallest id `epoch` is registered under.
+    pub fn id_of(&self, epoch: Epoch) -> Option<u8> {
+        (0..=u8::MAX).find(|&id| self.epochs[id as usize] == Some(epoch))
+    }
+}
+
+impl Default for EpochRegistry {
+    fn default() -> Self {
+        EpochRegistry::STANDARD
+    }
+}
+
+impl fmt::Display for EpochError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EpochError::InFuture(epoch) => write!(
+                f,
+                "epoch {} ms after the Unix epoch lies in the future",
+                epoch.unix_millis()
+            ),
+            EpochError::TooRecent(epoch) => write!(
+                f,
+                "epoch {} ms after the Unix epoch is the current time, use a fixed epoch instead",
+                epoch.unix_millis()
+            ),
+            EpochError::AlreadyRegistered(id) => {
+                write!(f, "a different epoch is already registered as {id}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for EpochError {}
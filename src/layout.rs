@@ -0,0 +1,268 @@
+use core::fmt;
+use core::time::Duration;
+
+/// The unit the timestamp of an Oat is counted in.
+///
+/// Coarser units trade resolution for range: with the standard 44 timestamp bits, milliseconds
+/// last for about 557 years while seconds last for more than 557,000 years. Every tick still
+/// offers the full range of sequence numbers, so coarse units also raise the throughput a bowl
+/// can sustain before it has to wait for the next tick.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum TimeUnit {
+    /// Milliseconds since the epoch.
+    Millis,
+    /// Ticks of 10 milliseconds since the epoch.
+    TenMillis,
+    /// Ticks of 100 milliseconds since the epoch.
+    HundredMillis,
+    /// Seconds since the epoch.
+    Seconds,
+}
+
+impl TimeUnit {
+    /// Returns the length of a single tick in milliseconds.
+    pub const fn as_millis(self) -> u64 {
+        match self {
+            TimeUnit::Millis => 1,
+            TimeUnit::TenMillis => 10,
+            TimeUnit::HundredMillis => 100,
+            TimeUnit::Seconds => 1000,
+        }
+    }
+
+    /// Returns the length of a single tick.
+    pub const fn tick(self) -> Duration {
+        Duration::from_millis(self.as_millis())
+    }
+
+    /// Converts a number of ticks into the duration they span.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::time::Duration;
+    /// use oats::layout::TimeUnit;
+    ///
+    /// assert_eq!(TimeUnit::TenMillis.to_duration(15), Duration::from_millis(150));
+    /// ```
+    pub const fn to_duration(self, ticks: u64) -> Duration {
+        let millis = ticks as u128 * self.as_millis() as u128;
+        Duration::new((millis / 1000) as u64, (millis % 1000) as u32 * 1_000_000)
+    }
+
+    /// Converts a duration into the number of whole ticks it spans.
+    pub const fn from_duration(self, duration: Duration) -> u64 {
+        (duration.as_millis() / self.as_millis() as u128) as u64
+    }
+}
+
+/// Describes how the 72 bits of an Oat are split between node, timestamp and sequence number.
+///
+/// The node occupies the most significant bits, the sequence number the least significant bits
+/// and the timestamp sits right above the sequence number. Bits in between are always zero. The
+/// `STANDARD` layout (8 node bits, 44 timestamp bits, 12 sequence bits in milliseconds) is the
+/// one used by `Oat::of` and `WrappedBowl::of`.
+///
+/// # Examples
+///
+/// ```
+/// use oats::layout::{Layout, TimeUnit};
+///
+/// // 1024 nodes with 16384 ids per millisecond for about 139 years.
+/// const WIDE: Layout = Layout::new(10, 42, 14, TimeUnit::Millis);
+///
+/// assert_eq!(WIDE.max_node(), 1023);
+/// assert_eq!(WIDE.max_seq(), 16383);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Layout {
+    node_bits: u8,
+    timestamp_bits: u8,
+    seq_bits: u8,
+    unit: TimeUnit,
+}
+
+/// The reasons a `Layout` can be rejected.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LayoutError {
+    /// The node needs between 1 and 16 bits.
+    InvalidNodeBits(u8),
+    /// The timestamp needs between 1 and 64 bits.
+    InvalidTimestampBits(u8),
+    /// The sequence number needs between 1 and 16 bits.
+    InvalidSeqBits(u8),
+    /// All fields together must fit into the 72 bits of an Oat.
+    TooWide(u32),
+}
+
+impl Layout {
+    /// The number of bits available in an Oat.
+    pub const BITS: u32 = 72;
+
+    /// The layout of `Oat::of`: 8 node bits, 44 timestamp bits and 12 sequence bits in milliseconds.
+    pub const STANDARD: Layout = Layout::new(8, 44, 12, TimeUnit::Millis);
+
+    /// A 53-bit layout whose `Oat64` values are safe integers in JavaScript: 8 node bits, 41
+    /// timestamp bits and 4 sequence bits in milliseconds.
+    ///
+    /// 41 bits of milliseconds last for about 69 years, so pair it with a recent epoch.
+    pub const JS_SAFE: Layout = Layout::new(8, 41, 4, TimeUnit::Millis);
+
+    /// Creates a new layout with the given field widths.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the widths do not fit, see `try_new`. When used to initialize a constant, this
+    /// turns an invalid layout into a compile-time error.
+    pub const fn new(node_bits: u8, timestamp_bits: u8, seq_bits: u8, unit: TimeUnit) -> Self {
+        match Layout::try_new(node_bits, timestamp_bits, seq_bits, unit) {
+            Ok(layout) => layout,
+            Err(_) => panic!("Invalid Oat layout."),
+        }
+    }
+
+    /// Creates a new layout with the given field widths, checking that they fit into an Oat.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::layout::{Layout, LayoutError, TimeUnit};
+    ///
+    /// assert!(Layout::try_new(16, 40, 16, TimeUnit::Millis).is_ok());
+    /// assert_eq!(
+    ///     Layout::try_new(16, 44, 16, TimeUnit::Millis),
+    ///     Err(LayoutError::TooWide(76))
+    /// );
+    /// ```
+    pub const fn try_new(
+        node_bits: u8,
+        timestamp_bits: u8,
+        seq_bits: u8,
+        unit: TimeUnit,
+    ) -> Result<Self, LayoutError> {
+        if node_bits == 0 || node_bits > 16 {
+            return Err(LayoutError::InvalidNodeBits(node_bits));
+        }
+        if timestamp_bits == 0 || timestamp_bits > 64 {
+            return Err(LayoutError::InvalidTimestampBits(timestamp_bits));
+        }
+        if seq_bits == 0 || seq_bits > 16 {
+            return Err(LayoutError::InvalidSeqBits(seq_bits));
+        }
+
+        let total = node_bits as u32 + timestamp_bits as u32 + seq_bits as u32;
+        if total > Layout::BITS {
+            return Err(LayoutError::TooWide(total));
+        }
+
+        Ok(Layout {
+            node_bits,
+            timestamp_bits,
+            seq_bits,
+            unit,
+        })
+    }
+
+    /// Returns the number of bits used by the node.
+    pub const fn node_bits(&self) -> u8 {
+        self.node_bits
+    }
+
+    /// Returns the number of bits used by the timestamp.
+    pub const fn timestamp_bits(&self) -> u8 {
+        self.timestamp_bits
+    }
+
+    /// Returns the number of bits used by the sequence number.
+    pub const fn seq_bits(&self) -> u8 {
+        self.seq_bits
+    }
+
+    /// Returns the number of bits used by all fields together.
+    pub const fn total_bits(&self) -> u32 {
+        self.node_bits as u32 + self.timestamp_bits as u32 + self.seq_bits as u32
+    }
+
+    /// Returns the unit the timestamp is counted in.
+    pub const fn unit(&self) -> TimeUnit {
+        self.unit
+    }
+
+    /// Returns a copy of the layout counting timestamps in `unit`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::layout::{Layout, TimeUnit};
+    ///
+    /// const ARCHIVAL: Layout = Layout::STANDARD.with_unit(TimeUnit::Seconds);
+    ///
+    /// assert_eq!(ARCHIVAL.unit(), TimeUnit::Seconds);
+    /// assert_eq!(ARCHIVAL.timestamp_bits(), 44);
+    /// ```
+    pub const fn with_unit(self, unit: TimeUnit) -> Self {
+        Layout { unit, ..self }
+    }
+
+    /// Returns the largest node id the layout can hold.
+    pub const fn max_node(&self) -> u16 {
+        ((1u32 << self.node_bits) - 1) as u16
+    }
+
+    /// Returns the largest timestamp the layout can hold.
+    pub const fn max_timestamp(&self) -> u64 {
+        (((1u128) << self.timestamp_bits) - 1) as u64
+    }
+
+    /// Returns the largest sequence number the layout can hold.
+    pub const fn max_seq(&self) -> u16 {
+        ((1u32 << self.seq_bits) - 1) as u16
+    }
+
+    /// Packs the fields into the 72-bit value of an Oat.
+    pub(crate) const fn pack(&self, node: u16, seq: u16, timestamp: u64) -> u128 {
+        ((node as u128) << (Layout::BITS - self.node_bits as u32))
+            | ((timestamp as u128) << self.seq_bits)
+            | seq as u128
+    }
+
+    /// Extracts the node from the 72-bit value of an Oat.
+    pub(crate) const fn node_of(&self, raw: u128) -> u16 {
+        (raw >> (Layout::BITS - self.node_bits as u32)) as u16 & self.max_node()
+    }
+
+    /// Extracts the sequence number from the 72-bit value of an Oat.
+    pub(crate) const fn seq_of(&self, raw: u128) -> u16 {
+        raw as u16 & self.max_seq()
+    }
+
+    /// Extracts the timestamp from the 72-bit value of an Oat.
+    pub(crate) const fn timestamp_of(&self, raw: u128) -> u64 {
+        (raw >> self.seq_bits) as u64 & self.max_timestamp()
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout::STANDARD
+    }
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutError::InvalidNodeBits(bits) => {
+                write!(f, "node bits must be between 1 and 16, got {bits}")
+            }
+            LayoutError::InvalidTimestampBits(bits) => {
+                write!(f, "timestamp bits must be between 1 and 64, got {bits}")
+            }
+            LayoutError::InvalidSeqBits(bits) => {
+                write!(f, "sequence bits must be between 1 and 16, got {bits}")
+            }
+            LayoutError::TooWide(bits) => write!(f, "layout needs {bits} bits, only 72 fit"),
+        }
+    }
+}
+
+impl core::error::Error for LayoutError {}
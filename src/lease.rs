@@ -0,0 +1,126 @@
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::{fmt, process};
+
+/// A node id claimed by this process through an advisory file lock.
+///
+/// Every process on a host that should mint Oats points at the same lease directory and
+/// acquires a lease on startup. The lock is held for as long as the lease is alive and released
+/// when it is dropped, or by the operating system when the process exits.
+///
+/// Lock files are never removed, as deleting a file another process is about to lock would let
+/// two processes claim the same node.
+#[derive(Debug)]
+pub struct NodeLease {
+    node: u8,
+    path: PathBuf,
+    file: File,
+}
+
+/// The reasons a node can not be leased.
+#[derive(Debug)]
+pub enum LeaseError {
+    /// Every node in the range is leased by another process.
+    Exhausted(RangeInclusive<u8>),
+    /// The lease directory or one of its lock files could not be used.
+    Io(io::Error),
+}
+
+impl NodeLease {
+    /// Claims the first free node in `nodes`, using lock files in `dir`.
+    ///
+    /// The directory is created if it does not exist yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory shared by all processes on the host.
+    /// * `nodes` - The range of node ids this host may hand out.
+    ///
+    /// # Returns
+    ///
+    /// The lease for the claimed node, or a `LeaseError` if every node is taken.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::bowl::{GenerationBehavior, WrappedBowl};
+    /// use oats::lease::NodeLease;
+    ///
+    /// let dir = std::env::temp_dir().join("oats-lease-doctest");
+    /// let lease = NodeLease::acquire(&dir, 0..=15).expect("No free node left.");
+    ///
+    /// let wrapped_bowl = WrappedBowl::of(lease.node(), GenerationBehavior::Normal, None);
+    /// assert_eq!(wrapped_bowl.generate().node(), lease.node());
+    /// ```
+    pub fn acquire(dir: impl AsRef<Path>, nodes: RangeInclusive<u8>) -> Result<Self, LeaseError> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).map_err(LeaseError::Io)?;
+
+        for node in nodes.clone() {
+            let path = dir.join(format!("node-{node}.lock"));
+            let mut file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)
+                .map_err(LeaseError::Io)?;
+
+            match file.try_lock() {
+                Ok(()) => {
+                    // Record the owner to ease debugging, the lock itself is what counts.
+                    file.set_len(0).map_err(LeaseError::Io)?;
+                    writeln!(file, "{}", process::id()).map_err(LeaseError::Io)?;
+
+                    return Ok(NodeLease { node, path, file });
+                }
+                Err(TryLockError::WouldBlock) => continue,
+                Err(TryLockError::Error(err)) => return Err(LeaseError::Io(err)),
+            }
+        }
+
+        Err(LeaseError::Exhausted(nodes))
+    }
+
+    /// Returns the leased node id.
+    pub fn node(&self) -> u8 {
+        self.node
+    }
+
+    /// Returns the path of the lock file backing the lease.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for NodeLease {
+    fn drop(&mut self) {
+        // Closing the file releases the lock as well, unlocking explicitly just makes it prompt.
+        let _ = self.file.unlock();
+    }
+}
+
+impl fmt::Display for LeaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LeaseError::Exhausted(nodes) => write!(
+                f,
+                "all nodes from {} to {} are leased",
+                nodes.start(),
+                nodes.end()
+            ),
+            LeaseError::Io(err) => write!(f, "failed to lease node: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for LeaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LeaseError::Io(err) => Some(err),
+            LeaseError::Exhausted(_) => None,
+        }
+    }
+}
@@ -1,17 +1,73 @@
 //! This crate provides a simple and efficient way to generate globally unique identifiers.
+//!
+//! Without the default `std` feature the crate is `no_std`, leaving Oats, layouts and regions
+//! for encoding and decoding. The `alloc` feature adds `String` output on top.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 /// The bowl is used for generating Oat values in unified way.
+#[cfg(feature = "std")]
 pub mod bowl;
 
+/// The cipher module turns Oats into opaque tokens and back.
+pub mod cipher;
+
+/// The coordinator module assigns node ids across hosts.
+#[cfg(feature = "std")]
+pub mod coordinator;
+
+/// The epoch module defines the instants timestamps count from.
+pub mod epoch;
+
+/// The layout module describes how the bits of an Oat are split.
+pub mod layout;
+
+/// The lease module hands out node ids to processes sharing a host.
+#[cfg(feature = "std")]
+pub mod lease;
+
+/// The metrics module exposes the statistics of bowls to Prometheus.
+#[cfg(feature = "prometheus")]
+pub mod metrics;
+
 /// The oats are globally unique identifiers.
+///
+#[doc = include_str!("../docs/format.md")]
 pub mod oat;
 
+/// The oat64 module packs Oats into a single `u64`.
+pub mod oat64;
+
+/// The observer module reports what bowls are doing, for metrics and logging.
+#[cfg(feature = "std")]
+pub mod observer;
+
 /// The region moduele contains predefined regions
 pub mod region;
 
+/// The rng module provides the random numbers a bowl can start its sequences with.
+pub mod rng;
+
+/// The shard module routes Oats to shards and time partitions.
+pub mod shard;
+
+/// The signed module appends HMAC tags to Oats handed out as tokens.
+#[cfg(feature = "signed")]
+pub mod signed;
+
+/// The siphash module implements the keyed hash behind `Oat::stable_hash` and the cipher.
+pub mod siphash;
+
+/// The wire module defines a versioned binary encoding of Oats.
+pub mod wire;
+
 #[cfg(test)]
 mod tests {
     /// This module contains the implementation of the `bowl` module.
+    #[cfg(feature = "std")]
     mod bowl {
         use std::thread;
 
@@ -40,12 +96,33 @@ mod tests {
             let mut bowl: Bowl = Bowl::of(1, GenerationBehavior::Realtime, None);
 
             // Generate 10 Oat values with the Realtime generation behavior mode
-            let oats: Vec<u16> = (0..10).map(|_| bowl.new_seq()).collect();
+            let oats: Vec<u16> = (0..10).map(|_| bowl.new_seq().unwrap()).collect();
 
             // Check that the sequence numbers of the Oat values are increasing
             assert!(oats.windows(2).all(|window| window[0] < window[1]));
         }
 
+        /// Test that the legacy constructor refuses an epoch in the future with the epoch error.
+        #[test]
+        #[should_panic(expected = "Invalid epoch: epoch")]
+        fn test_wrapped_bowl_of_future_epoch() {
+            use std::time::{Duration, SystemTime};
+
+            let future = SystemTime::now() + Duration::from_secs(3600);
+            WrappedBowl::of(1, GenerationBehavior::Normal, Some(future));
+        }
+
+        /// Test that a realtime bowl keeps counting in its last tick after the clock stepped back.
+        #[test]
+        fn test_bowl_new_seq_realtime_clock_step_back() {
+            let mut bowl: Bowl = Bowl::of(1, GenerationBehavior::Realtime, None);
+            bowl.step_clock_back(1000);
+
+            let seqs: Vec<u16> = (0..10).map(|_| bowl.new_seq().unwrap()).collect();
+
+            assert_eq!(seqs, (1..=10).collect::<Vec<u16>>());
+        }
+
         /// Test function for generating Oat values using a WrappedBowl in a multi-threaded environment.
         #[test]
         fn test_wrapped_bowl_in_multi_thread_env() {
@@ -62,6 +139,292 @@ mod tests {
             handles.into_iter().for_each(|h| h.join().unwrap());
         }
 
+        /// Test that a UuidBowl generates strictly increasing UUIDv7 values carrying its node.
+        #[cfg(feature = "uuid")]
+        #[test]
+        fn test_uuid_bowl_generate() {
+            use std::time::{SystemTime, UNIX_EPOCH};
+
+            use crate::bowl::UuidBowl;
+            use uuid::Variant;
+
+            let uuid_bowl = UuidBowl::of(42);
+            let uuids: Vec<_> = (0..10_000).map(|_| uuid_bowl.generate()).collect();
+
+            assert!(uuids.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(uuids.iter().all(|uuid| uuid.get_version_num() == 7
+                && uuid.get_variant() == Variant::RFC4122
+                && UuidBowl::node_of(uuid) == Some(42)));
+
+            let (seconds, _) = uuids.last().unwrap().get_timestamp().unwrap().to_unix();
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+            assert!(now.as_secs().abs_diff(seconds) <= 1);
+            assert_eq!(
+                UuidBowl::node_of(&crate::oat::Oat::of(1, 0, 0).to_uuid()),
+                None
+            );
+        }
+
+        /// Test that `from_config` rejects nodes outside the layout and unfit epochs.
+        #[test]
+        fn test_wrapped_bowl_from_config() {
+            use std::time::{Duration, SystemTime};
+
+            use crate::bowl::{BowlConfig, BowlError};
+            use crate::epoch::{Epoch, EpochError};
+            use crate::layout::{Layout, TimeUnit};
+
+            let config = BowlConfig::of(1).with_epoch(Epoch::OATS);
+            let oat = WrappedBowl::from_config(config).unwrap().generate();
+            let age = SystemTime::now()
+                .duration_since(
+                    oat.instant_in(&Layout::STANDARD, Some(Epoch::OATS.to_system_time()))
+                        .unwrap(),
+                )
+                .unwrap();
+            assert_eq!(oat.node(), 1);
+            assert!(age < Duration::from_secs(1));
+
+            let narrow = Layout::new(4, 48, 12, TimeUnit::Millis);
+            assert_eq!(
+                WrappedBowl::from_config(
+                    config
+                        .with_layout(narrow)
+                        .with_mode(GenerationBehavior::Lazy)
+                )
+                .unwrap()
+                .generate()
+                .node_in(&narrow),
+                1
+            );
+            assert_eq!(
+                WrappedBowl::from_config(BowlConfig::of(16).with_layout(narrow)).err(),
+                Some(BowlError::InvalidNode(16))
+            );
+
+            let future = Epoch::from_system_time(SystemTime::now() + Duration::from_secs(3600));
+            assert_eq!(
+                WrappedBowl::from_config(config.with_epoch(future)).err(),
+                Some(BowlError::InvalidEpoch(EpochError::InFuture(future)))
+            );
+        }
+
+        /// Test that the horizon is the instant of the largest timestamp of the layout.
+        #[test]
+        fn test_wrapped_bowl_horizon() {
+            use std::time::{Duration, SystemTime};
+
+            use crate::bowl::BowlConfig;
+            use crate::epoch::Epoch;
+            use crate::layout::{Layout, TimeUnit};
+
+            let wrapped_bowl = WrappedBowl::of(1, GenerationBehavior::Normal, None);
+            assert_eq!(
+                wrapped_bowl.horizon(),
+                Some(SystemTime::UNIX_EPOCH + Duration::from_millis((1 << 44) - 1))
+            );
+
+            let coarse = Layout::STANDARD.with_unit(TimeUnit::Seconds);
+            let config = BowlConfig::of(1)
+                .with_epoch(Epoch::OATS)
+                .with_layout(coarse);
+            assert_eq!(
+                config.horizon(),
+                Some(Epoch::OATS.to_system_time() + Duration::from_secs((1 << 44) - 1))
+            );
+            assert_eq!(
+                WrappedBowl::from_config(config).unwrap().horizon(),
+                config.horizon()
+            );
+
+            // 64 bits of seconds reach beyond what `SystemTime` can represent.
+            let endless = Layout::new(4, 64, 4, TimeUnit::Seconds);
+            let config = BowlConfig::of(1).with_layout(endless);
+            assert_eq!(config.horizon(), None);
+            assert_eq!(WrappedBowl::from_config(config).unwrap().horizon(), None);
+        }
+
+        /// Test that a bowl past its horizon fails instead of panicking, and is refused by `from_config`.
+        #[test]
+        fn test_wrapped_bowl_exhausted() {
+            use std::time::{Duration, SystemTime};
+
+            use crate::bowl::{BowlConfig, BowlError, GenerateError};
+            use crate::epoch::Epoch;
+            use crate::layout::{Layout, TimeUnit};
+
+            // 30 bits of milliseconds since the Unix epoch ended in January 1970.
+            let short = Layout::new(8, 30, 12, TimeUnit::Millis);
+            let wrapped_bowl = WrappedBowl::with_layout(1, GenerationBehavior::Normal, None, short);
+            assert_eq!(wrapped_bowl.try_generate(), Err(GenerateError::Exhausted));
+            assert_eq!(
+                wrapped_bowl.try_generate_oat64(),
+                Err(GenerateError::Exhausted)
+            );
+            assert_eq!(
+                WrappedBowl::from_config(BowlConfig::of(1).with_layout(short)).err(),
+                Some(BowlError::HorizonTooClose(wrapped_bowl.horizon().unwrap()))
+            );
+
+            // 30 bits of seconds last for 34 years, starting 30 years ago.
+            let coarse = short.with_unit(TimeUnit::Seconds);
+            let epoch = Epoch::from_system_time(
+                SystemTime::now() - Duration::from_secs(30 * 365 * 24 * 60 * 60),
+            );
+            let config = BowlConfig::of(1).with_epoch(epoch).with_layout(coarse);
+            assert!(WrappedBowl::from_config(config).is_ok());
+            assert_eq!(
+                WrappedBowl::from_config(
+                    config.with_min_horizon(Duration::from_secs(5 * 365 * 24 * 60 * 60))
+                )
+                .err(),
+                Some(BowlError::HorizonTooClose(config.horizon().unwrap()))
+            );
+
+            // Horizons beyond `SystemTime` are always far enough away.
+            let endless = Layout::new(4, 64, 4, TimeUnit::Seconds);
+            assert!(WrappedBowl::from_config(BowlConfig::of(1).with_layout(endless)).is_ok());
+        }
+
+        /// Test that each overflow policy keeps the Oats strictly increasing, and how far it lets the bowl drift.
+        #[test]
+        fn test_sequence_overflow_policy() {
+            use std::time::Duration;
+
+            use crate::bowl::{BowlConfig, SequenceOverflowPolicy};
+            use crate::layout::{Layout, TimeUnit};
+
+            // 4 sequence numbers per 10 milliseconds.
+            let layout = Layout::new(8, 44, 2, TimeUnit::TenMillis);
+            let config = BowlConfig::of(1).with_layout(layout);
+
+            for (mode, policy, max_drift) in [
+                (
+                    GenerationBehavior::Normal,
+                    SequenceOverflowPolicy::Spin,
+                    Duration::ZERO,
+                ),
+                (
+                    GenerationBehavior::Realtime,
+                    SequenceOverflowPolicy::Sleep,
+                    Duration::ZERO,
+                ),
+                (
+                    GenerationBehavior::Normal,
+                    SequenceOverflowPolicy::Borrow(Duration::from_millis(30)),
+                    Duration::from_millis(40),
+                ),
+                (
+                    GenerationBehavior::Lazy,
+                    SequenceOverflowPolicy::Borrow(Duration::from_millis(30)),
+                    Duration::from_millis(40),
+                ),
+            ] {
+                let wrapped_bowl =
+                    WrappedBowl::from_config(config.with_mode(mode).with_overflow_policy(policy))
+                        .unwrap();
+                let oats: Vec<Oat> = (0..40).map(|_| wrapped_bowl.generate()).collect();
+
+                assert!(oats.windows(2).all(|pair| pair[0] < pair[1]), "{policy:?}");
+                assert!(wrapped_bowl.drift() <= max_drift, "{policy:?}");
+            }
+        }
+
+        /// Test that the `Fail` policy reports exhausted sequence numbers until the next tick.
+        #[test]
+        fn test_sequence_overflow_policy_fail() {
+            use std::time::Duration;
+
+            use crate::bowl::{BowlConfig, GenerateError, SequenceOverflowPolicy};
+            use crate::layout::{Layout, TimeUnit};
+
+            // 4 sequence numbers per second.
+            let layout = Layout::new(8, 44, 2, TimeUnit::Seconds);
+            let config = BowlConfig::of(1)
+                .with_layout(layout)
+                .with_overflow_policy(SequenceOverflowPolicy::Fail);
+            let wrapped_bowl = WrappedBowl::from_config(config).unwrap();
+
+            let results: Vec<_> = (0..8).map(|_| wrapped_bowl.try_generate()).collect();
+            assert!(results.contains(&Err(GenerateError::SequenceExhausted)));
+
+            thread::sleep(Duration::from_secs(1));
+            assert!(wrapped_bowl.try_generate().is_ok());
+        }
+
+        /// Test that a lazy bowl borrows time from the future without limit by default.
+        #[test]
+        fn test_lazy_drift() {
+            use std::time::Duration;
+
+            use crate::bowl::{BowlConfig, SequenceOverflowPolicy};
+            use crate::layout::{Layout, TimeUnit};
+
+            let layout = Layout::new(8, 44, 2, TimeUnit::Millis);
+            let config = BowlConfig::of(1)
+                .with_mode(GenerationBehavior::Lazy)
+                .with_layout(layout);
+            assert_eq!(
+                config.overflow_policy(),
+                SequenceOverflowPolicy::Borrow(Duration::MAX)
+            );
+
+            let wrapped_bowl = WrappedBowl::from_config(config).unwrap();
+            for _ in 0..400 {
+                wrapped_bowl.generate();
+            }
+            assert!(wrapped_bowl.drift() >= Duration::from_millis(50));
+        }
+
+        /// Test that every tick starts at the offset drawn from the random number generator.
+        #[test]
+        fn test_random_seq() {
+            use crate::rng::Rng;
+
+            /// Always returns the same number.
+            struct Fixed(u64);
+
+            impl Rng for Fixed {
+                fn next_u64(&mut self) -> u64 {
+                    self.0
+                }
+            }
+
+            // 5000 falls into the upper half of the 4096 sequence numbers, wrapping to 904.
+            let wrapped_bowl =
+                WrappedBowl::of(1, GenerationBehavior::Lazy, None).with_random_seq(Fixed(5000));
+            let oats: Vec<Oat> = (0..4096 - 905).map(|_| wrapped_bowl.generate()).collect();
+
+            assert_eq!(oats[0].seq(), 905);
+            assert_eq!(oats.last().unwrap().seq(), 4095);
+            assert!(oats.windows(2).all(|pair| pair[0] < pair[1]));
+
+            let oat = wrapped_bowl.generate();
+            assert_eq!(oat.seq(), 904);
+            assert_eq!(oat.timestamp(), oats[0].timestamp() + 1);
+        }
+
+        /// Test that a bowl with a seeded random starting point stays strictly increasing across ticks.
+        #[test]
+        fn test_random_seq_seeded() {
+            use crate::layout::{Layout, TimeUnit};
+            use crate::rng::SplitMix64;
+
+            let layout = Layout::new(8, 44, 4, TimeUnit::Millis);
+            for mode in [
+                GenerationBehavior::Lazy,
+                GenerationBehavior::Normal,
+                GenerationBehavior::Realtime,
+            ] {
+                let wrapped_bowl = WrappedBowl::with_layout(1, mode, None, layout)
+                    .with_random_seq(SplitMix64::new(7));
+                let oats: Vec<Oat> = (0..200).map(|_| wrapped_bowl.generate()).collect();
+
+                assert!(oats.windows(2).all(|pair| pair[0] < pair[1]), "{mode:?}");
+                assert!(oats.iter().any(|oat| oat.seq_in(&layout) != 0), "{mode:?}");
+            }
+        }
+
         /// Test function for generating Oat values using a WrappedBowl.
         fn test_wrapped_bowl_generate_definition(wrapped_bowl: WrappedBowl) {
             // Generate 10 Oat values
@@ -83,107 +446,1908 @@ mod tests {
         }
     }
 
-    /// The `oat` module contains tests for the `Oat` struct.
-    mod oat {
+    /// The `cipher` module contains tests for the `OatCipher`.
+    mod cipher {
+        use crate::cipher::OatCipher;
         use crate::oat::Oat;
 
-        /// Test the `to_string` method of the `Oat` struct.
+        /// The key `00 01 02 .. 0f`, as used by the SipHash reference vectors.
+        const KEY: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
+        /// Test the tokens of a few Oats under a fixed key.
         #[test]
-        fn test_to_string() {
-            let oat = Oat::of(1, 3, 1671800400_000);
-            let s = oat.to_string();
+        fn test_vectors() {
+            let cipher = OatCipher::of(KEY);
 
-            assert_eq!(s, "X1AwCIGvFTGAA");
+            for (oat, token) in [
+                ("X1AwCIGvFTGAA", "14IC_NU-8E1BE"),
+                ("X1BACIGvFTGAA", "20aqKXxhEc3CM"),
+                ("X0AAAAAAAAAAA", "55aRb1sZCdJyI"),
+                ("FF__________8", "67IhLbz7wwdAg"),
+            ] {
+                let oat = Oat::from_string(oat).unwrap();
+                let token = Oat::from_string(token).unwrap();
+
+                assert_eq!(cipher.encrypt(&oat), token);
+                assert_eq!(cipher.decrypt(&token), oat);
+            }
         }
 
-        /// Test the `to_bytes` method of the `Oat` struct.
+        /// Test that decrypting reverses encrypting, and that tokens depend on the key.
         #[test]
-        fn test_to_bytes() {
-            let oat = Oat::of(1, 3, 1671800400_000);
-            let b: [u8; 9] = oat.to_bytes();
+        fn test_round_trip() {
+            let cipher = OatCipher::of(KEY);
+            let other = OatCipher::of(*b"another key 1234");
 
-            assert_eq!(b, [1, 0x03, 0x0, 0x88, 0x1A, 0xF1, 0x53, 0x18, 0x0]);
+            for seq in 0..1000 {
+                let oat = Oat::of((seq % 256) as u8, seq, 1_671_800_400_000 + seq as u64);
+                let token = cipher.encrypt(&oat);
+
+                assert_eq!(cipher.decrypt(&token), oat);
+                assert_ne!(other.encrypt(&oat), token);
+                assert_ne!(other.decrypt(&token), oat);
+            }
         }
 
-        /// Test the `into` method of the `Oat` struct.
+        /// Test that the key does not show up in debug output.
         #[test]
-        fn test_into_bytes() {
-            let oat = Oat::of(1, 3, 1671800400_000);
-            let b: [u8; 9] = oat.into();
+        #[cfg(feature = "alloc")]
+        fn test_debug() {
+            use alloc::format;
 
-            assert_eq!(b, [1, 0x03, 0x0, 0x88, 0x1A, 0xF1, 0x53, 0x18, 0x0]);
+            assert_eq!(format!("{:?}", OatCipher::of(KEY)), "OatCipher { .. }");
         }
+    }
 
-        /// Test the `from_string` method of the `Oat` struct.
+    /// The `coordinator` module contains tests for the TCP coordinator and the `LeasedBowl`.
+    #[cfg(feature = "std")]
+    mod coordinator {
+        use std::thread;
+        use std::time::Duration;
+
+        use crate::bowl::{GenerateError, GenerationBehavior, LeasedBowl};
+        use crate::coordinator::{
+            AllocatorError, Coordinator, CoordinatorHandle, NodeAllocator, TcpAllocator, MAX_TTL,
+        };
+
+        const TTL: Duration = Duration::from_secs(30);
+
+        /// Starts a coordinator for the given nodes on an ephemeral localhost port.
+        fn coordinator(nodes: std::ops::RangeInclusive<u8>) -> CoordinatorHandle {
+            Coordinator::bind("127.0.0.1:0", nodes)
+                .unwrap()
+                .with_grace(Duration::from_millis(20))
+                .spawn()
+                .unwrap()
+        }
+
+        /// Test that clients receive distinct nodes until the range is exhausted.
         #[test]
-        fn test_from_string() {
-            let oat = Oat::from_string("X1AwCIGvFTGAA").unwrap();
+        fn test_acquire_distinct_nodes() {
+            let coordinator = coordinator(4..=5);
+            let mut first = TcpAllocator::connect(coordinator.local_addr()).unwrap();
+            let mut second = TcpAllocator::connect(coordinator.local_addr()).unwrap();
 
-            assert_eq!(oat.node(), 1);
-            assert_eq!(oat.seq(), 3);
-            assert_eq!(oat.timestamp(), 1671800400_000);
+            assert_eq!(first.acquire(TTL).unwrap().node(), 4);
+            assert_eq!(second.acquire(TTL).unwrap().node(), 5);
+            assert!(matches!(
+                second.acquire(TTL),
+                Err(AllocatorError::Exhausted)
+            ));
         }
 
-        /// Test the `from_string_unchecked` method of the `Oat` struct.
+        /// Test that a released node is handed out again.
         #[test]
-        fn test_from_string_unchecked() {
-            let oat = Oat::from_string_unchecked("X1AwCIGvFTGAA");
+        fn test_release() {
+            let coordinator = coordinator(0..=0);
+            let mut allocator = TcpAllocator::connect(coordinator.local_addr()).unwrap();
 
-            assert_eq!(oat.node(), 1);
-            assert_eq!(oat.seq(), 3);
-            assert_eq!(oat.timestamp(), 1671800400_000);
+            let lease = allocator.acquire(TTL).unwrap();
+            allocator.release(lease).unwrap();
+
+            let again = allocator.acquire(TTL).unwrap();
+            assert_eq!(again.node(), 0);
+            assert_ne!(again.token(), lease.token());
+            assert!(matches!(
+                allocator.release(lease),
+                Err(AllocatorError::Expired)
+            ));
         }
 
-        /// Test the `from_bytes` method of the `Oat` struct.
+        /// Test that renewing extends a lease but fails once it has expired.
         #[test]
-        fn test_from_bytes() {
-            let oat = Oat::from_bytes([1, 0x03, 0x0, 0x88, 0x1A, 0xF1, 0x53, 0x18, 0x0]);
+        fn test_renew_and_expire() {
+            let coordinator = coordinator(0..=0);
+            let mut allocator = TcpAllocator::connect(coordinator.local_addr()).unwrap();
 
-            assert_eq!(oat.node(), 1);
-            assert_eq!(oat.seq(), 3);
-            assert_eq!(oat.timestamp(), 1671800400_000);
+            let lease = allocator.acquire(Duration::from_millis(50)).unwrap();
+            let renewed = allocator.renew(&lease, TTL).unwrap();
+            assert_eq!(renewed.token(), lease.token());
+            assert!(renewed.deadline() > lease.deadline());
+
+            let short = allocator
+                .renew(&renewed, Duration::from_millis(10))
+                .unwrap();
+            thread::sleep(Duration::from_millis(50));
+            assert!(short.is_expired());
+            assert!(matches!(
+                allocator.renew(&short, TTL),
+                Err(AllocatorError::Expired)
+            ));
+
+            // After the grace period the node can be leased by someone else.
+            let mut other = TcpAllocator::connect(coordinator.local_addr()).unwrap();
+            assert_eq!(other.acquire(TTL).unwrap().node(), 0);
+
+            coordinator.shutdown().unwrap();
         }
 
-        /// Test the `from_bytes_ref` method of the `Oat` struct.
+        /// Test that the coordinator answers malformed requests with a protocol error.
         #[test]
-        fn test_from_bytes_ref() {
+        fn test_protocol_error() {
+            use std::io::{BufRead, BufReader, Write};
+            use std::net::TcpStream;
+
+            let coordinator = coordinator(0..=0);
+            let mut stream = TcpStream::connect(coordinator.local_addr()).unwrap();
+            writeln!(stream, "ACQUIRE soon").unwrap();
+
+            let mut line = String::new();
+            BufReader::new(stream).read_line(&mut line).unwrap();
+            assert_eq!(line, "ERR protocol invalid ttl\n");
+        }
+
+        /// Test that TTLs beyond `MAX_TTL` are rejected without harming the coordinator.
+        #[test]
+        fn test_huge_ttl() {
+            use std::io::{BufRead, BufReader, Write};
+            use std::net::TcpStream;
+
+            let coordinator = coordinator(0..=0);
+            let mut stream = TcpStream::connect(coordinator.local_addr()).unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+
+            writeln!(stream, "ACQUIRE {}", u64::MAX).unwrap();
+            reader.read_line(&mut line).unwrap();
+            assert_eq!(line, "ERR protocol invalid ttl\n");
+
+            let mut allocator = TcpAllocator::connect(coordinator.local_addr()).unwrap();
+            let lease = allocator.acquire(TTL).unwrap();
+            assert_eq!(lease.node(), 0);
+
+            line.clear();
+            writeln!(stream, "RENEW 0 {} {}", lease.token(), u64::MAX).unwrap();
+            reader.read_line(&mut line).unwrap();
+            assert_eq!(line, "ERR protocol invalid ttl\n");
+
+            assert!(matches!(
+                allocator.renew(&lease, MAX_TTL + Duration::from_millis(1)),
+                Err(AllocatorError::Protocol(_))
+            ));
+            assert!(matches!(
+                allocator.acquire(Duration::MAX),
+                Err(AllocatorError::Protocol(_))
+            ));
+            assert_eq!(allocator.renew(&lease, TTL).unwrap().node(), 0);
+        }
+
+        /// Test that tokens are not sequential, and that only broken listeners stop the coordinator.
+        #[test]
+        fn test_tokens_and_accept_errors() {
+            use std::io;
+
+            use crate::coordinator::is_transient;
+
+            let coordinator = coordinator(0..=1);
+            let mut allocator = TcpAllocator::connect(coordinator.local_addr()).unwrap();
+            let first = allocator.acquire(TTL).unwrap();
+            let second = allocator.acquire(TTL).unwrap();
+            assert_ne!(second.token().wrapping_sub(first.token()), 1);
+
+            assert!(is_transient(&io::ErrorKind::ConnectionAborted.into()));
+            assert!(is_transient(&io::ErrorKind::ConnectionReset.into()));
+            #[cfg(unix)]
+            assert!(is_transient(&io::Error::from_raw_os_error(24)));
+            assert!(!is_transient(&io::ErrorKind::InvalidInput.into()));
+        }
+
+        /// Test that a LeasedBowl refuses to generate once its lease expires.
+        #[test]
+        fn test_leased_bowl_expires() {
+            let coordinator = coordinator(9..=9);
+            let mut allocator = TcpAllocator::connect(coordinator.local_addr()).unwrap();
+
+            let lease = allocator.acquire(Duration::from_millis(50)).unwrap();
+            let leased_bowl = LeasedBowl::of(lease, GenerationBehavior::Normal, None);
+            assert_eq!(leased_bowl.generate().unwrap().node(), 9);
+
+            leased_bowl
+                .renew(&mut allocator, Duration::from_millis(10))
+                .unwrap();
+            thread::sleep(Duration::from_millis(50));
+
+            assert_eq!(
+                leased_bowl.generate().err(),
+                Some(GenerateError::LeaseExpired)
+            );
+        }
+    }
+
+    /// The `epoch` module contains tests for the `Epoch` struct and the `EpochRegistry`.
+    mod epoch {
+        use crate::epoch::{Epoch, EpochError, EpochRegistry};
+
+        /// Test the named epochs and the conversion from and to `SystemTime`.
+        #[test]
+        #[cfg(feature = "std")]
+        fn test_named_epochs() {
+            use std::time::{Duration, SystemTime};
+
+            assert_eq!(Epoch::UNIX.to_system_time(), SystemTime::UNIX_EPOCH);
+            assert_eq!(
+                Epoch::OATS.to_system_time(),
+                SystemTime::UNIX_EPOCH + Duration::from_secs(1_672_531_200)
+            );
+            assert_eq!(
+                Epoch::from_system_time(Epoch::OATS.to_system_time()),
+                Epoch::OATS
+            );
+        }
+
+        /// Test that epochs in the future or at the current time are rejected.
+        #[test]
+        #[cfg(feature = "std")]
+        fn test_validate() {
+            use std::time::{Duration, SystemTime};
+
+            assert_eq!(Epoch::UNIX.validate(), Ok(()));
+            assert_eq!(Epoch::OATS.validate(), Ok(()));
+
+            let past = Epoch::from_system_time(SystemTime::now() - Duration::from_secs(60));
+            assert_eq!(past.validate(), Ok(()));
+
+            let now = Epoch::from_system_time(SystemTime::now());
+            assert_eq!(now.validate(), Err(EpochError::TooRecent(now)));
+
+            let future = Epoch::from_system_time(SystemTime::now() + Duration::from_secs(60));
+            assert_eq!(future.validate(), Err(EpochError::InFuture(future)));
+        }
+
+        /// Test registering and looking up epochs by id.
+        #[test]
+        fn test_registry() {
+            let launch = Epoch::from_unix_millis(1_704_067_200_000);
+            let mut registry = EpochRegistry::STANDARD;
+
+            assert_eq!(registry.get(0), Some(Epoch::UNIX));
+            assert_eq!(registry.get(1), Some(Epoch::OATS));
+            assert_eq!(registry.get(2), None);
+            assert_eq!(registry.id_of(launch), None);
+
+            assert_eq!(registry.register(2, launch), Ok(()));
+            assert_eq!(registry.register(2, launch), Ok(()));
+            assert_eq!(
+                registry.register(1, launch),
+                Err(EpochError::AlreadyRegistered(1))
+            );
+
+            assert_eq!(registry.id_of(launch), Some(2));
+            assert_eq!(registry.get(1), Some(Epoch::OATS));
+            assert_eq!(EpochRegistry::new().get(0), None);
+        }
+    }
+
+    /// The `layout` module contains tests for the `Layout` struct.
+    mod layout {
+        #[cfg(feature = "std")]
+        use crate::bowl::{GenerationBehavior, WrappedBowl};
+        use crate::layout::{Layout, LayoutError, TimeUnit};
+        use crate::oat::Oat;
+
+        const WIDE: Layout = Layout::new(10, 42, 14, TimeUnit::Millis);
+
+        /// Test that the standard layout packs Oats exactly like `Oat::of`.
+        #[test]
+        fn test_standard_matches_of() {
+            let oat = Oat::with_layout(&Layout::STANDARD, 1, 3, 1_671_800_400_000);
+
+            assert_eq!(oat, Oat::of(1, 3, 1_671_800_400_000));
+            assert_eq!(oat.to_string(), "X1AwCIGvFTGAA");
+            assert_eq!(oat.node_in(&Layout::STANDARD), 1);
+            assert_eq!(oat.seq_in(&Layout::STANDARD), 3);
+            assert_eq!(oat.timestamp_in(&Layout::STANDARD), 1_671_800_400_000);
+            assert_eq!(Layout::default(), Layout::STANDARD);
+        }
+
+        /// Test that the fields of a custom layout round-trip at their maximum values.
+        #[test]
+        fn test_custom_layout_round_trip() {
             let oat =
-                Oat::from_bytes_ref(&[1, 0x03, 0x0, 0x88, 0x1A, 0xF1, 0x53, 0x18, 0x0]).unwrap();
+                Oat::with_layout(&WIDE, WIDE.max_node(), WIDE.max_seq(), WIDE.max_timestamp());
 
-            assert_eq!(oat.node(), 1);
-            assert_eq!(oat.seq(), 3);
-            assert_eq!(oat.timestamp(), 1671800400_000);
+            assert_eq!(oat.node_in(&WIDE), 1023);
+            assert_eq!(oat.seq_in(&WIDE), 0x3fff);
+            assert_eq!(oat.timestamp_in(&WIDE), (1 << 42) - 1);
+
+            let oat = Oat::from_bytes(oat.to_bytes());
+            assert_eq!(oat.node_in(&WIDE), 1023);
+            assert_eq!(Oat::with_layout(&WIDE, 0, 0, 0).to_bytes(), [0; 9]);
         }
 
-        /// Test the `from_bytes` method of the `Oat` struct with an invalid length.
+        /// Test that the full 72 bits can be used.
         #[test]
-        fn test_from_bytes_ref_invalid_length() {
-            let oat = Oat::from_bytes_ref(&[1, 0x03, 0x0, 0x88, 0x1A, 0xF1, 0x53, 0x18]);
+        fn test_full_width_layout() {
+            let layout = Layout::new(16, 40, 16, TimeUnit::Millis);
+            let oat = Oat::with_layout(&layout, 0xabcd, 0xffff, 0x12_3456_789a);
+
+            assert_eq!(oat.node_in(&layout), 0xabcd);
+            assert_eq!(oat.seq_in(&layout), 0xffff);
+            assert_eq!(oat.timestamp_in(&layout), 0x12_3456_789a);
+        }
+
+        /// Test that layouts that do not fit are rejected.
+        #[test]
+        fn test_invalid_layouts() {
+            let unit = TimeUnit::Millis;
 
-            assert!(oat.is_err());
             assert_eq!(
-                oat.err().unwrap(),
-                crate::oat::ParseOatError::InvalidLUIDLength(8)
+                Layout::try_new(0, 44, 12, unit),
+                Err(LayoutError::InvalidNodeBits(0))
+            );
+            assert_eq!(
+                Layout::try_new(17, 44, 11, unit),
+                Err(LayoutError::InvalidNodeBits(17))
+            );
+            assert_eq!(
+                Layout::try_new(8, 65, 0, unit),
+                Err(LayoutError::InvalidTimestampBits(65))
+            );
+            assert_eq!(
+                Layout::try_new(8, 44, 17, unit),
+                Err(LayoutError::InvalidSeqBits(17))
+            );
+            assert_eq!(
+                Layout::try_new(16, 48, 16, unit),
+                Err(LayoutError::TooWide(80))
             );
         }
 
+        /// Test that fields exceeding the layout are rejected.
         #[test]
         #[should_panic]
-        fn test_of_invalid_seq_length() {
-            let oat = Oat::of(1, 1 << 12, 0);
-            assert_eq!(oat.node(), 1);
-            assert_eq!(oat.seq(), 0x1000);
-            assert_eq!(oat.timestamp(), 0);
+        fn test_with_layout_invalid_node() {
+            Oat::with_layout(&WIDE, 1024, 0, 0);
         }
 
+        /// Test that a bowl wraps its sequence numbers according to its layout.
+        #[cfg(feature = "std")]
         #[test]
-        #[should_panic]
-        fn test_of_invalid_timestamp_length() {
-            let oat = Oat::of(1, 0xFF, 1 << 44);
-            assert_eq!(oat.node(), 1);
-            assert_eq!(oat.seq(), 0x1000);
-            assert_eq!(oat.timestamp(), 0);
+        fn test_bowl_with_layout() {
+            let layout = Layout::new(12, 48, 4, TimeUnit::Millis);
+            let wrapped_bowl =
+                WrappedBowl::with_layout(4000, GenerationBehavior::Lazy, None, layout);
+            assert_eq!(wrapped_bowl.layout(), layout);
+
+            let oats: Vec<Oat> = (0..16).map(|_| wrapped_bowl.generate()).collect();
+            assert!(oats.iter().all(|oat| oat.node_in(&layout) == 4000));
+            assert_eq!(oats[14].seq_in(&layout), 15);
+            assert_eq!(oats[15].seq_in(&layout), 0);
+            assert!(oats[15].timestamp_in(&layout) > oats[14].timestamp_in(&layout));
+        }
+
+        /// Test that bowls count coarse ticks and wait for the next tick once the sequence wraps.
+        #[cfg(feature = "std")]
+        #[test]
+        fn test_bowl_coarse_ticks() {
+            use std::time::{Duration, SystemTime};
+
+            for mode in [GenerationBehavior::Normal, GenerationBehavior::Realtime] {
+                let layout = Layout::new(8, 44, 2, TimeUnit::HundredMillis);
+                let wrapped_bowl = WrappedBowl::with_layout(1, mode, None, layout);
+
+                let before = SystemTime::now();
+                let oats: Vec<Oat> = (0..9).map(|_| wrapped_bowl.generate()).collect();
+                let after = SystemTime::now();
+
+                // Every tick offers 4 sequence numbers, so at least two ticks have passed.
+                let first = oats.first().unwrap().timestamp_in(&layout);
+                let last = oats.last().unwrap().timestamp_in(&layout);
+                assert!(last >= first + 2, "{mode:?}: {first} -> {last}");
+                assert!(oats.windows(2).all(|pair| {
+                    (pair[0].timestamp_in(&layout), pair[0].seq_in(&layout))
+                        < (pair[1].timestamp_in(&layout), pair[1].seq_in(&layout))
+                }));
+
+                let tick = Duration::from_millis(100);
+                let instant = oats.last().unwrap().instant_in(&layout, None).unwrap();
+                assert!(instant + tick > before && instant <= after);
+            }
+        }
+
+        /// Test that lazy bowls advance by a single tick of their unit.
+        #[cfg(feature = "std")]
+        #[test]
+        fn test_bowl_lazy_seconds() {
+            let layout = Layout::new(8, 44, 1, TimeUnit::Seconds);
+            let wrapped_bowl = WrappedBowl::with_layout(1, GenerationBehavior::Lazy, None, layout);
+
+            let first = wrapped_bowl.generate();
+            let second = wrapped_bowl.generate();
+            assert_eq!(
+                second.timestamp_in(&layout),
+                first.timestamp_in(&layout) + 1
+            );
+        }
+
+        /// Test the conversions between ticks and durations.
+        #[test]
+        fn test_time_unit_conversions() {
+            use std::time::Duration;
+
+            assert_eq!(
+                TimeUnit::Millis.to_duration(1500),
+                Duration::from_millis(1500)
+            );
+            assert_eq!(TimeUnit::Seconds.to_duration(3), Duration::from_secs(3));
+            assert_eq!(
+                TimeUnit::HundredMillis.from_duration(Duration::from_millis(1999)),
+                19
+            );
+            assert_eq!(TimeUnit::TenMillis.tick(), Duration::from_millis(10));
+        }
+
+        /// Test that a bowl rejects nodes that do not fit into its layout.
+        #[cfg(feature = "std")]
+        #[test]
+        #[should_panic]
+        fn test_bowl_with_layout_invalid_node() {
+            WrappedBowl::with_layout(256, GenerationBehavior::Normal, None, Layout::STANDARD);
+        }
+    }
+
+    /// The `lease` module contains tests for the `NodeLease` struct.
+    #[cfg(feature = "std")]
+    mod lease {
+        use std::path::PathBuf;
+        use std::{env, fs, process};
+
+        use crate::lease::{LeaseError, NodeLease};
+
+        /// Returns an empty lease directory unique to the test.
+        fn lease_dir(name: &str) -> PathBuf {
+            let dir = env::temp_dir().join(format!("oats-lease-{}-{name}", process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            dir
+        }
+
+        /// Test that concurrent leases receive distinct nodes from the range.
+        #[test]
+        fn test_acquire_distinct_nodes() {
+            let dir = lease_dir("distinct");
+            let first = NodeLease::acquire(&dir, 3..=5).unwrap();
+            let second = NodeLease::acquire(&dir, 3..=5).unwrap();
+            let third = NodeLease::acquire(&dir, 3..=5).unwrap();
+
+            assert_eq!(first.node(), 3);
+            assert_eq!(second.node(), 4);
+            assert_eq!(third.node(), 5);
+            assert!(first.path().exists());
+        }
+
+        /// Test that acquiring fails once every node of the range is leased.
+        #[test]
+        fn test_acquire_exhausted() {
+            let dir = lease_dir("exhausted");
+            let _lease = NodeLease::acquire(&dir, 0..=0).unwrap();
+
+            let err = NodeLease::acquire(&dir, 0..=0).unwrap_err();
+            assert!(matches!(err, LeaseError::Exhausted(nodes) if nodes == (0..=0)));
+        }
+
+        /// Test that dropping a lease makes its node available again.
+        #[test]
+        fn test_release_on_drop() {
+            let dir = lease_dir("release");
+            let first = NodeLease::acquire(&dir, 0..=1).unwrap();
+            let second = NodeLease::acquire(&dir, 0..=1).unwrap();
+            assert_eq!(second.node(), 1);
+
+            drop(first);
+            let third = NodeLease::acquire(&dir, 0..=1).unwrap();
+            assert_eq!(third.node(), 0);
+        }
+    }
+
+    /// The `metrics` module contains tests for the Prometheus exposition of the `BowlRegistry`.
+    #[cfg(feature = "prometheus")]
+    mod metrics {
+        use crate::bowl::{BowlConfig, GenerationBehavior, WrappedBowl};
+        use crate::epoch::Epoch;
+        use crate::layout::{Layout, TimeUnit};
+        use crate::metrics::BowlRegistry;
+
+        /// Test the rendered statistics of two idle bowls against the golden output.
+        #[test]
+        fn test_golden() {
+            let first =
+                WrappedBowl::from_config(BowlConfig::of(1).with_epoch(Epoch::OATS)).unwrap();
+            let second = WrappedBowl::of(2, GenerationBehavior::Realtime, None);
+
+            let registry = BowlRegistry::new();
+            registry.register(&first);
+            registry.register(&second);
+            registry.register(&first.clone());
+
+            for _ in 0..3 {
+                first.generate();
+            }
+
+            assert_eq!(registry.render(), include_str!("../data/metrics.prom"));
+        }
+
+        /// Test that the wait histogram of a busy bowl is consistent with its counters.
+        #[test]
+        fn test_wait_histogram() {
+            let config = BowlConfig::of(3)
+                .with_mode(GenerationBehavior::Realtime)
+                .with_epoch(Epoch::OATS)
+                .with_layout(Layout::new(8, 44, 4, TimeUnit::Millis));
+            let wrapped_bowl = WrappedBowl::from_config(config).unwrap();
+            for _ in 0..200 {
+                wrapped_bowl.generate();
+            }
+
+            let registry = BowlRegistry::new();
+            registry.register(&wrapped_bowl);
+            let metrics = registry.render();
+            let value = |name: &str| -> f64 {
+                let line = metrics.lines().find(|line| line.starts_with(name)).unwrap();
+                line.rsplit_once(' ').unwrap().1.parse().unwrap()
+            };
+
+            let stats = wrapped_bowl.stats();
+            let labels = "node=\"3\",epoch=\"1672531200000\"";
+            assert_eq!(value(&format!("oats_ids_issued_total{{{labels}}}")), 200.0);
+            assert_eq!(
+                value(&format!("oats_wait_seconds_count{{{labels}}}")),
+                stats.waits() as f64
+            );
+            assert_eq!(
+                value(&format!("oats_wait_seconds_bucket{{{labels},le=\"+Inf\"}}")),
+                stats.waits() as f64
+            );
+            assert_eq!(
+                value(&format!("oats_wait_seconds_sum{{{labels}}}")),
+                stats.wait_time().as_secs_f64()
+            );
+
+            let buckets = stats.wait_buckets();
+            assert!(buckets.windows(2).all(|pair| pair[0] <= pair[1]));
+            assert_eq!(buckets[buckets.len() - 1], stats.waits());
+        }
+
+        /// Test that a bowl with an epoch before the Unix epoch is scraped without panicking.
+        #[test]
+        fn test_epoch_before_unix() {
+            use std::time::{Duration, SystemTime};
+
+            let epoch = SystemTime::UNIX_EPOCH - Duration::from_secs(24 * 60 * 60);
+            let wrapped_bowl = WrappedBowl::of(1, GenerationBehavior::Normal, Some(epoch));
+            assert_eq!(wrapped_bowl.epoch(), None);
+
+            let registry = BowlRegistry::new();
+            registry.register(&wrapped_bowl);
+            wrapped_bowl.generate();
+
+            let metrics = registry.render();
+            assert!(metrics.contains("oats_ids_issued_total{node=\"1\",epoch=\"\"} 1\n"));
+            assert!(metrics.contains("oats_drift_seconds{node=\"1\",epoch=\"\"} 0\n"));
+        }
+
+        /// Test that unregistered bowls and empty registries only leave the family headers.
+        #[test]
+        fn test_unregister() {
+            let wrapped_bowl = WrappedBowl::of(1, GenerationBehavior::Normal, None);
+            let registry = BowlRegistry::new();
+            registry.register(&wrapped_bowl);
+            registry.unregister(&wrapped_bowl);
+
+            let metrics = registry.render();
+            assert_eq!(metrics, BowlRegistry::new().render());
+            assert!(metrics.lines().all(|line| line.starts_with("# ")));
+            assert_eq!(metrics.lines().count(), 10);
+        }
+    }
+
+    /// The `oat` module contains tests for the `Oat` struct.
+    mod oat {
+        use crate::oat::Oat;
+
+        /// Test the `to_string` method of the `Oat` struct.
+        #[test]
+        fn test_to_string() {
+            let oat = Oat::of(1, 3, 1_671_800_400_000);
+            let s = oat.to_string();
+
+            assert_eq!(s, "X1AwCIGvFTGAA");
+        }
+
+        /// Test the `to_bytes` method of the `Oat` struct.
+        #[test]
+        fn test_to_bytes() {
+            let oat = Oat::of(1, 3, 1_671_800_400_000);
+            let b: [u8; 9] = oat.to_bytes();
+
+            assert_eq!(b, [1, 0x03, 0x0, 0x88, 0x1A, 0xF1, 0x53, 0x18, 0x0]);
+        }
+
+        /// Test the `into` method of the `Oat` struct.
+        #[test]
+        fn test_into_bytes() {
+            let oat = Oat::of(1, 3, 1_671_800_400_000);
+            let b: [u8; 9] = oat.into();
+
+            assert_eq!(b, [1, 0x03, 0x0, 0x88, 0x1A, 0xF1, 0x53, 0x18, 0x0]);
+        }
+
+        /// Test the `encode_to` method of the `Oat` struct for small and large nodes.
+        #[test]
+        fn test_encode_to() {
+            let mut buffer = [0; Oat::ENCODED_LEN];
+
+            assert_eq!(
+                Oat::of(1, 3, 1_671_800_400_000).encode_to(&mut buffer),
+                "X1AwCIGvFTGAA"
+            );
+            assert_eq!(Oat::of(0xab, 0, 0).encode_to(&mut buffer), "ABAAAAAAAAAAA");
+            assert_eq!(Oat::of(0xab, 0, 0).to_string(), "ABAAAAAAAAAAA");
+        }
+
+        /// Test that `from_ascii` decodes what `encode_to` and `write_to` produce.
+        #[test]
+        fn test_from_ascii_round_trip() {
+            let mut line = String::new();
+
+            for node in 0..=255 {
+                let oat = Oat::of(node, 0xfff, 0xfff_ffff_ffff - node as u64);
+                let mut buffer = [0; Oat::ENCODED_LEN];
+
+                assert_eq!(
+                    Oat::from_ascii(oat.encode_to(&mut buffer).as_bytes()),
+                    Ok(oat)
+                );
+
+                line.clear();
+                oat.write_to(&mut line).unwrap();
+                assert_eq!(Oat::from_ascii(line.as_bytes()), Ok(oat));
+            }
+        }
+
+        /// Test that `from_ascii` points at the first invalid byte.
+        #[test]
+        fn test_from_ascii_invalid() {
+            use crate::oat::ParseOatError;
+
+            assert_eq!(
+                Oat::from_ascii(b"X1AwCIGvFTGA"),
+                Err(ParseOatError::InvalidLength(12))
+            );
+            assert_eq!(
+                Oat::from_ascii(b"G1AwCIGvFTGAA"),
+                Err(ParseOatError::InvalidCharacter(0))
+            );
+            assert_eq!(
+                Oat::from_ascii(b"XXAwCIGvFTGAA"),
+                Err(ParseOatError::InvalidCharacter(1))
+            );
+            assert_eq!(
+                Oat::from_ascii(b"X1AwCIGvFTG+A"),
+                Err(ParseOatError::InvalidCharacter(11))
+            );
+            // The last character carries two bits beyond the eight bytes.
+            assert_eq!(
+                Oat::from_ascii(b"X1AwCIGvFTGAB"),
+                Err(ParseOatError::InvalidCharacter(12))
+            );
+        }
+
+        /// Test that constructors and accessors of the `Oat` struct work in constants.
+        #[test]
+        fn test_const_fns() {
+            const SYSTEM: Oat = Oat::of(1, 3, 1_671_800_400_000);
+            const BYTES: [u8; 9] = SYSTEM.to_bytes();
+            const FIELDS: (u8, u16, u64) = (SYSTEM.node(), SYSTEM.seq(), SYSTEM.timestamp());
+            const COPY: Oat = Oat::from_bytes(BYTES);
+
+            assert_eq!(FIELDS, (1, 3, 1_671_800_400_000));
+            assert_eq!(COPY, SYSTEM);
+            assert_eq!(crate::oat!("X1AwCIGvFTGAA"), SYSTEM);
+        }
+
+        /// Test the bounds and the nil value of the `Oat` struct.
+        #[test]
+        fn test_min_max_nil() {
+            assert_eq!(Oat::NIL, Oat::of(0, 0, 0));
+            assert_eq!(Oat::MIN, Oat::NIL);
+            assert_eq!(Oat::MAX.to_bytes(), [0xff; 9]);
+            assert_eq!(Oat::MAX.to_string(), "FF__________8");
+            assert!(Oat::MIN < Oat::of(0, 1, 0));
+            assert!(Oat::of(0xff, 0xfff, 0xfff_ffff_ffff) < Oat::MAX);
+            assert!(Oat::of(0, 0, 1) < Oat::of(1, 0, 0));
+        }
+
+        /// Test the canonical node encoding against the vectors for every node.
+        #[test]
+        fn test_node_vectors() {
+            let vectors = include_str!("../data/node-vectors.tsv")
+                .lines()
+                .filter(|line| !line.starts_with('#'));
+
+            let mut count = 0;
+            for (node, line) in vectors.enumerate() {
+                let columns: Vec<&str> = line.split('\t').collect();
+                let oat = Oat::of(node as u8, 3, 1_671_800_400_000);
+
+                assert_eq!(columns[0], node.to_string());
+                assert_eq!(&oat.to_string()[..2], columns[1]);
+                assert_eq!(oat.to_string(), columns[2]);
+                assert_eq!(Oat::from_string(columns[2]), Ok(oat));
+                assert_eq!(Oat::from_ascii(columns[2].as_bytes()), Ok(oat));
+                assert_eq!(Oat::from_string_lenient(columns[2]), Ok(oat));
+                count += 1;
+            }
+
+            assert_eq!(count, 256);
+        }
+
+        /// Test that the strict parsers reject non-canonical nodes, which the lenient parser accepts.
+        #[test]
+        fn test_non_canonical_nodes() {
+            use crate::oat::ParseOatError;
+
+            let cases = [
+                ("01", 1, 0),
+                ("1X", 16, 1),
+                ("XX", 0, 1),
+                ("x1", 1, 0),
+                ("1a", 26, 1),
+                ("0F", 15, 0),
+                ("+1", 1, 0),
+            ];
+
+            for (node, legacy, index) in cases {
+                let string = format!("{node}AwCIGvFTGAA");
+
+                assert_eq!(
+                    Oat::from_string(&string),
+                    Err(ParseOatError::InvalidCharacter(index)),
+                    "{string}"
+                );
+                assert_eq!(
+                    Oat::from_ascii(string.as_bytes()),
+                    Err(ParseOatError::InvalidCharacter(index)),
+                    "{string}"
+                );
+                assert_eq!(
+                    Oat::from_string_lenient(&string).map(|oat| oat.node()),
+                    Ok(legacy),
+                    "{string}"
+                );
+            }
+
+            assert_eq!(Oat::from_string("X"), Err(ParseOatError::InvalidLength(1)));
+            assert_eq!(
+                Oat::from_string_lenient("Xé"),
+                Err(ParseOatError::InvalidLength(3))
+            );
+        }
+
+        /// Test that `from_string` reports the length of LUIDs that are too long or too short.
+        #[test]
+        fn test_from_string_invalid_length() {
+            use crate::oat::ParseOatError;
+
+            assert_eq!(
+                Oat::from_string("X1AwCIGvFTGA"),
+                Err(ParseOatError::InvalidLUIDLength(7))
+            );
+            assert_eq!(
+                Oat::from_string("X1AwCIGvFTGAAAAAAAAAAAAA"),
+                Err(ParseOatError::InvalidLUIDLength(16))
+            );
+        }
+
+        /// Test the `from_string` method of the `Oat` struct.
+        #[test]
+        fn test_from_string() {
+            let oat = Oat::from_string("X1AwCIGvFTGAA").unwrap();
+
+            assert_eq!(oat.node(), 1);
+            assert_eq!(oat.seq(), 3);
+            assert_eq!(oat.timestamp(), 1_671_800_400_000);
+        }
+
+        /// Test the `from_string_unchecked` method of the `Oat` struct.
+        #[test]
+        fn test_from_string_unchecked() {
+            let oat = Oat::from_string_unchecked("X1AwCIGvFTGAA");
+
+            assert_eq!(oat.node(), 1);
+            assert_eq!(oat.seq(), 3);
+            assert_eq!(oat.timestamp(), 1_671_800_400_000);
+        }
+
+        /// Test the `from_bytes` method of the `Oat` struct.
+        #[test]
+        fn test_from_bytes() {
+            let oat = Oat::from_bytes([1, 0x03, 0x0, 0x88, 0x1A, 0xF1, 0x53, 0x18, 0x0]);
+
+            assert_eq!(oat.node(), 1);
+            assert_eq!(oat.seq(), 3);
+            assert_eq!(oat.timestamp(), 1_671_800_400_000);
+        }
+
+        /// Test the `from_bytes_ref` method of the `Oat` struct.
+        #[test]
+        fn test_from_bytes_ref() {
+            let oat =
+                Oat::from_bytes_ref(&[1, 0x03, 0x0, 0x88, 0x1A, 0xF1, 0x53, 0x18, 0x0]).unwrap();
+
+            assert_eq!(oat.node(), 1);
+            assert_eq!(oat.seq(), 3);
+            assert_eq!(oat.timestamp(), 1_671_800_400_000);
+        }
+
+        /// Test the `from_bytes` method of the `Oat` struct with an invalid length.
+        #[test]
+        fn test_from_bytes_ref_invalid_length() {
+            let oat = Oat::from_bytes_ref(&[1, 0x03, 0x0, 0x88, 0x1A, 0xF1, 0x53, 0x18]);
+
+            assert!(oat.is_err());
+            assert_eq!(
+                oat.err().unwrap(),
+                crate::oat::ParseOatError::InvalidLUIDLength(8)
+            );
+        }
+
+        /// Test that `to_u128` and `from_u128` round-trip every field of the `Oat` struct.
+        #[test]
+        fn test_u128_round_trip() {
+            let oat = Oat::of(0xff, 0xfff, 0xfff_ffff_ffff);
+            let value: u128 = oat.into();
+
+            assert_eq!(value, 0xff << 64 | ((1 << 56) - 1));
+            assert_eq!(Oat::from_u128(value), Some(oat));
+            assert_eq!(Oat::try_from(1u128 << 72), Err(1 << 72));
+        }
+
+        /// Test that UUIDs derived from an Oat carry version 8 and convert back losslessly.
+        #[cfg(feature = "uuid")]
+        #[test]
+        fn test_uuid_round_trip() {
+            use uuid::{Uuid, Variant};
+
+            let oat = Oat::of(0xab, 0xfff, 0xfff_ffff_ffff);
+
+            for uuid in [oat.to_uuid(), oat.to_uuid_ordered()] {
+                assert_eq!(uuid.get_version_num(), 8);
+                assert_eq!(uuid.get_variant(), Variant::RFC4122);
+            }
+            assert_eq!(Oat::try_from(Uuid::from(oat)), Ok(oat));
+            assert_eq!(Oat::from_uuid_ordered(oat.to_uuid_ordered()), Ok(oat));
+        }
+
+        /// Test that the ordered UUID mapping sorts by timestamp, then sequence, then node.
+        #[cfg(feature = "uuid")]
+        #[test]
+        fn test_uuid_ordered_sorting() {
+            let oats = [
+                Oat::of(0xff, 0, 1_671_800_400_000),
+                Oat::of(0x00, 1, 1_671_800_400_000),
+                Oat::of(0x01, 1, 1_671_800_400_000),
+                Oat::of(0x00, 0, 1_671_800_400_001),
+            ];
+
+            let uuids: Vec<_> = oats.iter().map(Oat::to_uuid_ordered).collect();
+            assert!(uuids.windows(2).all(|pair| pair[0] < pair[1]));
+
+            let strings: Vec<_> = uuids.iter().map(ToString::to_string).collect();
+            assert!(strings.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+
+        /// Test that UUIDs not derived from an Oat, or from the other mapping, are rejected.
+        #[cfg(feature = "uuid")]
+        #[test]
+        fn test_uuid_rejects_foreign() {
+            use crate::oat::UuidError;
+            use uuid::Uuid;
+
+            let oat = Oat::of(1, 3, 1_671_800_400_000);
+            let v4 = Uuid::from_u128(0x9b2d_6a1c_4f3e_4a8b_9c7d_1e2f_3a4b_5c6d);
+
+            assert_eq!(Oat::from_uuid(v4), Err(UuidError::InvalidVersion(4)));
+            assert_eq!(
+                Oat::from_uuid(Uuid::max()),
+                Err(UuidError::InvalidVersion(15))
+            );
+            assert_eq!(
+                Oat::from_uuid(oat.to_uuid_ordered()),
+                Err(UuidError::InvalidPadding)
+            );
+            assert_eq!(
+                Oat::from_uuid_ordered(oat.to_uuid()),
+                Err(UuidError::InvalidPadding)
+            );
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_of_invalid_seq_length() {
+            let oat = Oat::of(1, 1 << 12, 0);
+            assert_eq!(oat.node(), 1);
+            assert_eq!(oat.seq(), 0x1000);
+            assert_eq!(oat.timestamp(), 0);
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_of_invalid_timestamp_length() {
+            let oat = Oat::of(1, 0xFF, 1 << 44);
+            assert_eq!(oat.node(), 1);
+            assert_eq!(oat.seq(), 0x1000);
+            assert_eq!(oat.timestamp(), 0);
+        }
+
+        /// Test the stable hash against the vectors of the format specification.
+        #[test]
+        fn test_stable_hash_vectors() {
+            let key = core::array::from_fn(|i| i as u8);
+
+            for (oat, keyed, zeros) in [
+                ("X1AwCIGvFTGAA", 0x5f02ce13ab67ef21, 0xa537b135611671e6),
+                ("X2AwCIGvFTGAA", 0x2b969f24561be25e, 0x0fe89fc1325a5f43),
+                ("X0AAAAAAAAAAA", 0x4876b84f02298863, 0x2d508cd20c8f0cef),
+                ("FF__________8", 0x30f4333b0ba8a712, 0xbe29d70ddcf7e35d),
+            ] {
+                let oat = Oat::from_string(oat).unwrap();
+
+                assert_eq!(oat.stable_hash(&key), keyed, "{oat}");
+                assert_eq!(oat.stable_hash(&[0; 16]), zeros, "{oat}");
+            }
+        }
+
+        /// Test that `Hash` covers the node, so Oats differing only in their node hash differently.
+        #[test]
+        fn test_hash_covers_node() {
+            use std::hash::{BuildHasher, RandomState};
+
+            let state = RandomState::new();
+            let first = Oat::of(1, 3, 1_671_800_400_000);
+            let second = Oat::of(2, 3, 1_671_800_400_000);
+
+            assert_ne!(state.hash_one(first), state.hash_one(second));
+            assert_eq!(state.hash_one(first), state.hash_one(first));
+        }
+    }
+
+    /// The `oat64` module contains tests for Oats packed into a `u64`.
+    mod oat64 {
+        #[cfg(feature = "std")]
+        use std::time::{Duration, SystemTime};
+
+        #[cfg(feature = "std")]
+        use crate::bowl::{GenerationBehavior, WrappedBowl};
+        use crate::layout::{Layout, TimeUnit};
+        use crate::oat::Oat;
+        use crate::oat64::{Oat64, Oat64Error};
+
+        /// Test that the standard layout packs the fields into all 64 bits.
+        #[test]
+        fn test_standard_round_trip() {
+            let oat = Oat::of(0xff, 0xfff, 0xfff_ffff_ffff);
+            let oat64 = Oat64::try_from(oat).unwrap();
+
+            assert_eq!(u64::from(oat64), u64::MAX);
+            assert_eq!(oat64.node(), 0xff);
+            assert_eq!(oat64.seq(), 0xfff);
+            assert_eq!(oat64.timestamp(), 0xfff_ffff_ffff);
+            assert_eq!(Oat::from(oat64), oat);
+            assert_eq!(Oat::from(Oat64::from(0)), Oat::of(0, 0, 0));
+        }
+
+        /// Test that Oats which do not fit are rejected.
+        #[test]
+        fn test_from_oat_rejects() {
+            let stray = Oat::from_bytes([1, 0, 0, 0, 0, 0, 0, 0, 0x80]);
+            assert_eq!(Oat64::try_from(stray), Err(Oat64Error::NotInLayout));
+
+            let wide = Layout::new(16, 40, 16, TimeUnit::Millis);
+            let oat = Oat::with_layout(&wide, 1, 0, 0);
+            assert_eq!(Oat64::from_oat(&oat, &wide), Err(Oat64Error::TooWide(72)));
+        }
+
+        /// Test that Oat64 values can be built and taken apart in constants, like Oats.
+        #[test]
+        fn test_const() {
+            const OAT64: Oat64 = Oat64::of(1, 3, 1_671_800_400_000);
+            const JS_SAFE: Oat64 = Oat64::with_layout(&Layout::JS_SAFE, 255, 15, (1 << 41) - 1);
+            const FIELDS: (u8, u16, u64) = (OAT64.node(), OAT64.seq(), OAT64.timestamp());
+            const RAW: u64 = Oat64::from_u64(0x0118_53f1_1a88_0003).to_u64();
+            const PACKED: Result<Oat64, Oat64Error> =
+                Oat64::from_oat(&Oat::of(1, 3, 1_671_800_400_000), &Layout::STANDARD);
+
+            assert_eq!(FIELDS, (1, 3, 1_671_800_400_000));
+            assert_eq!(RAW, OAT64.to_u64());
+            assert_eq!(PACKED, Ok(OAT64));
+            assert_eq!(JS_SAFE.to_u64(), (1 << 53) - 1);
+            assert_eq!(
+                OAT64.to_oat(&Layout::STANDARD),
+                Oat::of(1, 3, 1_671_800_400_000)
+            );
+        }
+
+        /// Test that a JS-safe bowl generates increasing Oat64 values below 2^53.
+        #[cfg(feature = "std")]
+        #[test]
+        fn test_generate_js_safe() {
+            let epoch = SystemTime::now() - Duration::from_secs(60);
+            let wrapped_bowl = WrappedBowl::with_layout(
+                0xff,
+                GenerationBehavior::Normal,
+                Some(epoch),
+                Layout::JS_SAFE,
+            );
+
+            let oats: Vec<Oat64> = (0..100).map(|_| wrapped_bowl.generate_oat64()).collect();
+
+            assert!(oats.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(oats
+                .iter()
+                .all(|oat| oat.to_u64() < (1 << 53) && oat.node_in(&Layout::JS_SAFE) == 0xff));
+
+            let oat = oats[0].to_oat(&Layout::JS_SAFE);
+            assert_eq!(Oat64::from_oat(&oat, &Layout::JS_SAFE), Ok(oats[0]));
+        }
+
+        /// Test that generating an Oat64 with a layout wider than 64 bits fails, leaving the bowl usable.
+        #[cfg(feature = "std")]
+        #[test]
+        fn test_generate_too_wide() {
+            use crate::bowl::{BowlConfig, GenerateError};
+            use crate::epoch::Epoch;
+
+            let wide = Layout::new(16, 40, 16, TimeUnit::Millis);
+            let config = BowlConfig::of(1).with_epoch(Epoch::OATS).with_layout(wide);
+            let wrapped_bowl = WrappedBowl::from_config(config).unwrap();
+
+            assert_eq!(
+                wrapped_bowl.try_generate_oat64(),
+                Err(GenerateError::LayoutTooWide(72))
+            );
+            assert_eq!(wrapped_bowl.try_generate().unwrap().node_in(&wide), 1);
+            assert_eq!(wrapped_bowl.stats().generated(), 1);
+        }
+    }
+
+    /// The `observer` module contains tests for the `BowlObserver` and the counters of bowls.
+    #[cfg(feature = "std")]
+    mod observer {
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        use crate::bowl::{
+            BowlConfig, GenerateError, GenerationBehavior, SequenceOverflowPolicy, WrappedBowl,
+        };
+        use crate::epoch::Epoch;
+        use crate::layout::{Layout, TimeUnit};
+        use crate::oat::Oat;
+        use crate::observer::BowlObserver;
+        #[cfg(feature = "tracing")]
+        use std::sync::atomic::{AtomicU64, Ordering};
+        #[cfg(feature = "tracing")]
+        use tracing::span::{Attributes, Id, Record};
+        #[cfg(feature = "tracing")]
+        use tracing::{Metadata, Subscriber};
+
+        /// A layout with 16 sequence numbers per millisecond, running out almost at once.
+        const TINY_SEQ: Layout = Layout::new(8, 44, 4, TimeUnit::Millis);
+
+        /// The events an observer received, in order.
+        #[derive(Debug, Clone, PartialEq)]
+        enum Event {
+            Generated(Oat),
+            Wrapped(u64),
+            Waited(Duration),
+            Regressed(Duration),
+        }
+
+        /// An observer recording every event.
+        #[derive(Debug, Clone, Default)]
+        struct Recorder(Arc<Mutex<Vec<Event>>>);
+
+        impl BowlObserver for Recorder {
+            fn generated(&self, oat: &Oat) {
+                self.0.lock().unwrap().push(Event::Generated(*oat));
+            }
+
+            fn sequence_wrapped(&self, timestamp: u64) {
+                self.0.lock().unwrap().push(Event::Wrapped(timestamp));
+            }
+
+            fn waited(&self, waited: Duration) {
+                self.0.lock().unwrap().push(Event::Waited(waited));
+            }
+
+            fn clock_regressed(&self, behind: Duration) {
+                self.0.lock().unwrap().push(Event::Regressed(behind));
+            }
+        }
+
+        fn tiny_bowl(mode: GenerationBehavior) -> BowlConfig {
+            BowlConfig::of(1)
+                .with_mode(mode)
+                .with_epoch(Epoch::OATS)
+                .with_layout(TINY_SEQ)
+        }
+
+        /// Test that a fresh bowl has not counted anything yet.
+        #[test]
+        fn test_fresh_stats() {
+            let wrapped_bowl =
+                WrappedBowl::from_config(tiny_bowl(GenerationBehavior::Normal)).unwrap();
+            let stats = wrapped_bowl.stats();
+
+            assert_eq!(stats.generated(), 0);
+            assert_eq!(stats.wraps(), 0);
+            assert_eq!(stats.waits(), 0);
+            assert_eq!(stats.wait_time(), Duration::ZERO);
+            assert_eq!(stats.clock_regressions(), 0);
+        }
+
+        /// Test that the counters see the wraps and waits of a bowl running out of sequence numbers.
+        #[test]
+        fn test_spin_stats() {
+            let wrapped_bowl =
+                WrappedBowl::from_config(tiny_bowl(GenerationBehavior::Realtime)).unwrap();
+            for _ in 0..200 {
+                wrapped_bowl.generate();
+                wrapped_bowl.generate_oat64();
+            }
+
+            let stats = wrapped_bowl.stats();
+            assert_eq!(stats.generated(), 400);
+            assert!(stats.wraps() >= 1);
+            assert!(stats.waits() >= 1 && stats.waits() <= stats.wraps());
+            assert!(stats.wait_time() > Duration::ZERO);
+            assert_eq!(stats.clock_regressions(), 0);
+        }
+
+        /// Test that borrowing time from the future wraps without waiting.
+        #[test]
+        fn test_lazy_stats() {
+            let wrapped_bowl =
+                WrappedBowl::from_config(tiny_bowl(GenerationBehavior::Lazy)).unwrap();
+            for _ in 0..200 {
+                wrapped_bowl.generate();
+            }
+
+            let stats = wrapped_bowl.stats();
+            assert_eq!(stats.generated(), 200);
+            assert!(stats.wraps() >= 12);
+            assert_eq!(stats.waits(), 0);
+        }
+
+        /// Test that failed attempts count neither as wraps nor as generated Oats.
+        #[test]
+        fn test_fail_stats() {
+            let config = tiny_bowl(GenerationBehavior::Normal)
+                .with_overflow_policy(SequenceOverflowPolicy::Fail);
+            let wrapped_bowl = WrappedBowl::from_config(config).unwrap();
+
+            // Keep the clock behind the last timestamp, so every retry fails.
+            wrapped_bowl.step_clock_back(60_000);
+            for _ in 0..15 {
+                wrapped_bowl.try_generate().unwrap();
+            }
+            for _ in 0..10 {
+                assert_eq!(
+                    wrapped_bowl.try_generate(),
+                    Err(GenerateError::SequenceExhausted)
+                );
+            }
+
+            let stats = wrapped_bowl.stats();
+            assert_eq!(stats.generated(), 15);
+            assert_eq!(stats.wraps(), 0);
+            assert_eq!(stats.waits(), 0);
+        }
+
+        /// Test that the observer receives the same events the counters count, shared by all clones.
+        #[test]
+        fn test_observer_events() {
+            let recorder = Recorder::default();
+            let wrapped_bowl = WrappedBowl::from_config(tiny_bowl(GenerationBehavior::Normal))
+                .unwrap()
+                .with_observer(recorder.clone());
+
+            let clone = wrapped_bowl.clone();
+            let oats: Vec<Oat> = (0..100).map(|_| clone.generate()).collect();
+
+            let events = recorder.0.lock().unwrap().clone();
+            let generated: Vec<Oat> = events
+                .iter()
+                .filter_map(|event| match event {
+                    Event::Generated(oat) => Some(*oat),
+                    _ => None,
+                })
+                .collect();
+            let count =
+                |matches: fn(&Event) -> bool| events.iter().filter(|e| matches(e)).count() as u64;
+
+            let stats = wrapped_bowl.stats();
+            assert_eq!(generated, oats);
+            assert_eq!(count(|e| matches!(e, Event::Wrapped(_))), stats.wraps());
+            assert_eq!(count(|e| matches!(e, Event::Waited(_))), stats.waits());
+            assert_eq!(
+                count(|e| matches!(e, Event::Regressed(_))),
+                stats.clock_regressions()
+            );
+            assert_eq!(stats.generated(), 100);
+        }
+
+        /// Test that the default callbacks do nothing.
+        #[test]
+        fn test_default_callbacks() {
+            struct Silent;
+            impl BowlObserver for Silent {}
+
+            let wrapped_bowl = WrappedBowl::from_config(tiny_bowl(GenerationBehavior::Normal))
+                .unwrap()
+                .with_observer(Silent);
+            for _ in 0..100 {
+                wrapped_bowl.generate();
+            }
+
+            assert_eq!(wrapped_bowl.stats().generated(), 100);
+        }
+
+        /// Test that the tracing observer reports into the generate span without disturbing the bowl.
+        #[cfg(feature = "tracing")]
+        #[test]
+        fn test_tracing_observer() {
+            use crate::observer::TracingObserver;
+
+            let wrapped_bowl = WrappedBowl::from_config(tiny_bowl(GenerationBehavior::Realtime))
+                .unwrap()
+                .with_observer(TracingObserver);
+            for _ in 0..100 {
+                wrapped_bowl.generate();
+            }
+
+            assert_eq!(wrapped_bowl.stats().generated(), 100);
+        }
+
+        /// Records for every event the number of spans it was emitted in.
+        #[cfg(feature = "tracing")]
+        #[derive(Default)]
+        struct Depths {
+            next_id: AtomicU64,
+            entered: Mutex<u64>,
+            events: Arc<Mutex<Vec<u64>>>,
+        }
+
+        #[cfg(feature = "tracing")]
+        impl Subscriber for Depths {
+            fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, _span: &Attributes<'_>) -> Id {
+                Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+            }
+
+            fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+            fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+            fn event(&self, _event: &tracing::Event<'_>) {
+                let depth = *self.entered.lock().unwrap();
+                self.events.lock().unwrap().push(depth);
+            }
+
+            fn enter(&self, _span: &Id) {
+                *self.entered.lock().unwrap() += 1;
+            }
+
+            fn exit(&self, _span: &Id) {
+                *self.entered.lock().unwrap() -= 1;
+            }
+        }
+
+        /// Test that legacy constructors warn about an epoch at the current time through `tracing`.
+        #[cfg(feature = "tracing")]
+        #[test]
+        fn test_tracing_epoch_warning() {
+            use std::time::SystemTime;
+
+            let subscriber = Depths::default();
+            let events = Arc::clone(&subscriber.events);
+
+            tracing::subscriber::with_default(subscriber, || {
+                WrappedBowl::of(
+                    1,
+                    GenerationBehavior::Normal,
+                    Some(Epoch::OATS.to_system_time()),
+                );
+                assert_eq!(events.lock().unwrap().len(), 0);
+
+                WrappedBowl::of(1, GenerationBehavior::Normal, Some(SystemTime::now()));
+                assert_eq!(events.lock().unwrap().len(), 1);
+            });
+        }
+
+        /// Test that the events of the tracing observer, `generated` included, fall within the generate span.
+        #[cfg(feature = "tracing")]
+        #[test]
+        fn test_tracing_span_scope() {
+            use crate::observer::TracingObserver;
+
+            let subscriber = Depths::default();
+            let events = Arc::clone(&subscriber.events);
+            let wrapped_bowl = WrappedBowl::from_config(tiny_bowl(GenerationBehavior::Realtime))
+                .unwrap()
+                .with_observer(TracingObserver);
+
+            tracing::subscriber::with_default(subscriber, || {
+                wrapped_bowl.generate();
+                wrapped_bowl.generate_oat64();
+            });
+
+            assert_eq!(*events.lock().unwrap(), [1, 1]);
+        }
+    }
+
+    /// The `wire` module contains tests for the versioned binary encoding.
+    mod wire {
+        use crate::layout::{Layout, LayoutError, TimeUnit};
+        use crate::oat::Oat;
+        use crate::wire::{ByteOrder, WireError, WireHeader};
+
+        /// Test the exact bytes of the versioned encoding in both byte orders.
+        #[test]
+        fn test_to_bytes_v() {
+            let oat = Oat::of(1, 3, 1_671_800_400_000);
+
+            let little = oat.to_bytes_v(&WireHeader::default());
+            assert_eq!(
+                &*little,
+                [0x10, 8, 44, 12, 1, 0x03, 0x0, 0x88, 0x1A, 0xF1, 0x53, 0x18, 0x0]
+            );
+
+            let big = oat.to_bytes_v(
+                &WireHeader::default()
+                    .with_epoch_id(9)
+                    .with_order(ByteOrder::Big),
+            );
+            assert_eq!(
+                &*big,
+                [0x13, 8, 44, 12, 9, 0x00, 0x18, 0x53, 0xF1, 0x1A, 0x88, 0x00, 0x03, 0x01]
+            );
+        }
+
+        /// Test that both forms round-trip for custom layouts, and legacy bytes still decode.
+        #[test]
+        fn test_round_trip() {
+            let layout = Layout::new(10, 42, 14, TimeUnit::Seconds);
+            let oat = Oat::with_layout(&layout, 1000, 0x3fff, 1_671_800_400);
+
+            for order in [ByteOrder::Little, ByteOrder::Big] {
+                let header = WireHeader::of(layout).with_order(order);
+                assert_eq!(
+                    Oat::from_bytes_v(&oat.to_bytes_v(&header)),
+                    Ok((oat, header))
+                );
+
+                let header = header.with_epoch_id(255);
+                assert_eq!(
+                    Oat::from_bytes_v(&oat.to_bytes_v(&header)),
+                    Ok((oat, header))
+                );
+            }
+
+            assert_eq!(
+                Oat::from_bytes_v(&oat.to_bytes()),
+                Ok((oat, WireHeader::default()))
+            );
+        }
+
+        /// Test that the big-endian form sorts by time, whatever the node.
+        #[test]
+        fn test_big_endian_sorts_by_time() {
+            let header = WireHeader::default().with_order(ByteOrder::Big);
+            let oats = [
+                Oat::of(0xff, 0, 1_671_800_400_000),
+                Oat::of(0x00, 1, 1_671_800_400_000),
+                Oat::of(0x00, 0, 1_671_800_400_001),
+            ];
+
+            let encoded: Vec<_> = oats.iter().map(|oat| oat.to_bytes_v(&header)).collect();
+            assert!(encoded.windows(2).all(|pair| *pair[0] < *pair[1]));
+        }
+
+        /// Test that malformed input is rejected.
+        #[test]
+        fn test_invalid() {
+            let oat = Oat::of(1, 3, 1_671_800_400_000);
+            let bytes = oat.to_bytes_v(&WireHeader::default().with_order(ByteOrder::Big));
+
+            assert_eq!(
+                Oat::from_bytes_v(&bytes[..12]),
+                Err(WireError::InvalidLength(12))
+            );
+            assert_eq!(
+                Oat::from_bytes_v(&[0; 14]),
+                Err(WireError::UnsupportedVersion(0))
+            );
+
+            let mut broken = bytes.to_vec();
+            broken[0] = 0x14;
+            assert_eq!(
+                Oat::from_bytes_v(&broken),
+                Err(WireError::UnsupportedFlags(4))
+            );
+
+            let mut broken = bytes.to_vec();
+            broken[1] = 17;
+            assert_eq!(
+                Oat::from_bytes_v(&broken),
+                Err(WireError::InvalidLayout(LayoutError::InvalidNodeBits(17)))
+            );
+
+            let mut broken = bytes.to_vec();
+            broken[3] |= 0x80;
+            assert_eq!(Oat::from_bytes_v(&broken), Err(WireError::InvalidUnit(4)));
+
+            let mut broken = bytes.to_vec();
+            broken[4] = 0x80;
+            assert_eq!(Oat::from_bytes_v(&broken), Err(WireError::NotInLayout));
+        }
+
+        /// Test that little-endian payloads must fit the layout of the header, like big-endian ones.
+        #[test]
+        fn test_little_endian_not_in_layout() {
+            // The standard timestamp reaches into the bits between node and timestamp of JS_SAFE.
+            let oat = Oat::of(1, 3, 1_671_800_400_000);
+            let bytes = oat.to_bytes_v(&WireHeader::of(Layout::JS_SAFE));
+            assert_eq!(Oat::from_bytes_v(&bytes), Err(WireError::NotInLayout));
+
+            let fitting = Oat::with_layout(&Layout::JS_SAFE, 1, 3, 1_000_000);
+            let bytes = fitting.to_bytes_v(&WireHeader::of(Layout::JS_SAFE));
+            assert_eq!(
+                Oat::from_bytes_v(&bytes),
+                Ok((fitting, WireHeader::of(Layout::JS_SAFE)))
+            );
+        }
+
+        /// Test that only registered epoch ids are accepted when decoding with a registry.
+        #[test]
+        fn test_epoch_id_registry() {
+            use crate::epoch::EpochRegistry;
+
+            let oat = Oat::of(1, 3, 1_671_800_400_000);
+            let header = WireHeader::default().with_epoch_id(2);
+            let bytes = oat.to_bytes_v(&header);
+
+            assert_eq!(Oat::from_bytes_v(&bytes), Ok((oat, header)));
+            assert_eq!(
+                Oat::from_bytes_v_in(&bytes, &EpochRegistry::STANDARD),
+                Err(WireError::UnknownEpoch(2))
+            );
+            assert_eq!(
+                Oat::from_bytes_v_in(&Oat::to_bytes(&oat), &EpochRegistry::new()),
+                Ok((oat, WireHeader::default()))
+            );
+        }
+    }
+
+    /// The `region` module contains tests for the generated `Region` table.
+    mod region {
+        use std::collections::HashSet;
+
+        use crate::region::Region;
+
+        /// Prefixes of the regions shipped before the table was generated, in discriminant order.
+        const LEGACY_PREFIXES: &str = "AG AN AY BG BI BK C DA DB DF DG DI DN DR DT DX EB ED EE EF \
+            EG EH EI EK EL EN EP ES ET EV EY FA FB FC FD FE FG FH FI FJ FK FL FM FN FO FP FQ FS FT \
+            FV FW FX FY FZ GA GB GC GE GF GG GL GM GO GQ GS GU GV HA HB HC HD HE HH HJ HK HL HR HS \
+            HT HU K LA LB LC LD LE LF LG LH LI LJ LK LL LM LN LO LP LQ LR LS LT LU LV LW LX LY LZ \
+            MB MD MG MH MK MM MN MP MR MS MT MU MW MY MZ NC NF NG NI NL NS NT NV NW NZ OA OB OE OI \
+            OJ OK OL OM OO OP OR OS OT OY PA PB PC PF PG PH PJ PK PL PM PO PP PT PW RC RJ RK RO RP \
+            SA SB SC SD SE SF SG SH SI SJ SK SL SM SN SO SP SS SU SV SW SY TA TB TD TF TG TI TJ TK \
+            TL TN TQ TR TT TU TV TX U UA UB UC UD UG UK UM UT VA VC VD VE VG VH VI VL VM VN VO VQ \
+            VR VT VV VY WA WB WI WM WP WQ WR WS Y Z ZK ZM";
+
+        /// Test that discriminants of previously released regions never change.
+        #[test]
+        fn test_discriminants_are_stable() {
+            for (discriminant, prefix) in LEGACY_PREFIXES.split_whitespace().enumerate() {
+                let region = Region::from_prefix(prefix).expect("Legacy region was removed.");
+                assert_eq!(region as usize, discriminant, "{prefix} was renumbered");
+            }
+        }
+
+        /// Test that `ALL` is ordered by discriminant and round-trips through `TryFrom<u8>`.
+        #[test]
+        fn test_all_matches_discriminants() {
+            for (discriminant, region) in Region::ALL.iter().enumerate() {
+                assert_eq!(*region as usize, discriminant);
+                assert_eq!(Region::try_from(discriminant as u8), Ok(*region));
+            }
+
+            assert_eq!(
+                Region::try_from(Region::ALL.len() as u8),
+                Err(Region::ALL.len() as u8)
+            );
+        }
+
+        /// Test that every prefix is used by exactly one region.
+        #[test]
+        fn test_prefixes_are_unique() {
+            let mut prefixes = HashSet::new();
+
+            assert!(Region::ALL
+                .iter()
+                .all(|region| prefixes.insert(region.prefix())));
+        }
+
+        /// Test that longest-prefix matching can reach every region and resolves its own prefix to it.
+        #[test]
+        fn test_prefixes_do_not_overlap() {
+            for region in Region::ALL {
+                assert_eq!(Region::from_icao(region.prefix()), Some(*region));
+
+                // Find a complete location indicator that is not claimed by a longer prefix.
+                let reachable = (b'A'..=b'Z').any(|letter| {
+                    let mut code = region.prefix().to_string();
+                    while code.len() < 4 {
+                        code.push(letter as char);
+                    }
+
+                    Region::from_icao(&code) == Some(*region)
+                });
+                assert!(
+                    reachable,
+                    "{} is shadowed by longer prefixes",
+                    region.prefix()
+                );
+            }
+        }
+
+        /// Test that aliases point to a canonical region covering the same territories.
+        #[test]
+        fn test_aliases_are_canonical() {
+            for region in Region::ALL {
+                let canonical = region.canonical();
+
+                assert_eq!(canonical.canonical(), canonical);
+                assert!(region
+                    .iso_codes()
+                    .iter()
+                    .all(|code| canonical.iso_codes().contains(code)));
+            }
+
+            assert_eq!(Region::ET.canonical(), Region::ED);
+            assert_eq!(Region::WR.canonical(), Region::WA);
+        }
+
+        /// Test the `from_icao` method with real location indicators.
+        #[test]
+        fn test_from_icao() {
+            assert_eq!(Region::from_icao("KJFK"), Some(Region::K));
+            assert_eq!(Region::from_icao("CYYZ"), Some(Region::C));
+            assert_eq!(Region::from_icao("eddf"), Some(Region::ED));
+            assert_eq!(Region::from_icao("UUEE"), Some(Region::UU));
+            assert_eq!(Region::from_icao("UMMS"), Some(Region::UM));
+            assert_eq!(Region::from_icao("UMKK"), Some(Region::UMK));
+            assert_eq!(Region::from_icao("NFTF"), Some(Region::NFT));
+            assert_eq!(Region::from_icao("QQQQ"), None);
+            assert_eq!(Region::from_icao("EDDFX"), None);
+            assert_eq!(Region::from_icao(""), None);
+        }
+    }
+
+    /// The `rng` module contains tests for the `SplitMix64` generator.
+    mod rng {
+        use crate::rng::{Rng, SplitMix64};
+
+        /// Test the reference output of SplitMix64.
+        #[test]
+        fn test_split_mix_64() {
+            let mut rng = SplitMix64::new(1234567);
+            let numbers: [u64; 5] = core::array::from_fn(|_| rng.next_u64());
+
+            assert_eq!(
+                numbers,
+                [
+                    6457827717110365317,
+                    3203168211198807973,
+                    9817491932198370423,
+                    4593380528125082431,
+                    16408922859458223821,
+                ]
+            );
+        }
+
+        /// Test that the same seed yields the same numbers.
+        #[test]
+        fn test_split_mix_64_seeded() {
+            let mut first = SplitMix64::new(42);
+            let mut second = first.clone();
+
+            assert!((0..100).all(|_| first.next_u64() == second.next_u64()));
+            assert_ne!(SplitMix64::new(1).next_u64(), SplitMix64::new(2).next_u64());
+        }
+    }
+
+    /// The `shard` module contains tests for shard routing and the `TimePartitioner`.
+    mod shard {
+        use crate::epoch::Epoch;
+        use crate::layout::{Layout, TimeUnit};
+        use crate::oat::Oat;
+        use crate::oat64::Oat64;
+        use crate::shard::{jump_consistent_hash, Granularity, TimePartitioner};
+
+        /// Test the jump consistent hash and the shards of Oats against an independent implementation.
+        #[test]
+        fn test_vectors() {
+            for (key, buckets, bucket) in [
+                (0, 1, 0),
+                (1, 10, 6),
+                (0xdead_beef, 10, 5),
+                (u64::MAX, 1000, 313),
+                (42, 7, 2),
+            ] {
+                assert_eq!(jump_consistent_hash(key, buckets), bucket);
+            }
+
+            for (oat, shards) in [
+                ("X1AwCIGvFTGAA", [0, 0, 76]),
+                ("X2AwCIGvFTGAA", [1, 5, 527]),
+                ("X0AAAAAAAAAAA", [1, 15, 670]),
+                ("FF__________8", [0, 5, 431]),
+            ] {
+                let oat = Oat::from_string(oat).unwrap();
+                assert_eq!([oat.shard(2), oat.shard(16), oat.shard(1000)], shards);
+            }
+        }
+
+        /// Test that consecutive Oats of a single node spread evenly over the shards.
+        #[test]
+        fn test_balance() {
+            let mut counts = [0u32; 16];
+            for i in 0..64_000u64 {
+                let oat = Oat::of(1, (i % 4096) as u16, 1_671_800_400_000 + i / 4096);
+                counts[oat.shard(16) as usize] += 1;
+            }
+
+            // 4000 per shard, with a standard deviation of about 60.
+            assert!(
+                counts.iter().all(|&count| (3700..4300).contains(&count)),
+                "{counts:?}"
+            );
+        }
+
+        /// Test that adding a shard only moves Oats into the new shard.
+        #[test]
+        fn test_growth() {
+            let mut moved = 0;
+            for seq in 0..4096 {
+                let oat = Oat::of(7, seq, 1_671_800_400_000);
+                let (before, after) = (oat.shard(10), oat.shard(11));
+
+                assert!(before == after || after == 10);
+                moved += (before != after) as u32;
+            }
+
+            // About one in eleven Oats moves.
+            assert!((250..500).contains(&moved), "{moved}");
+        }
+
+        /// Test that keyed routing differs from the default, and that Oat64s route like their Oats.
+        #[test]
+        fn test_keyed_and_oat64() {
+            let oats: Vec<Oat> = (0..100)
+                .map(|seq| Oat::of(1, seq, 1_671_800_400_000))
+                .collect();
+
+            assert!(oats
+                .iter()
+                .any(|oat| oat.shard_with_key(1000, b"0123456789abcdef") != oat.shard(1000)));
+            assert!(oats
+                .iter()
+                .all(|oat| Oat64::try_from(*oat).unwrap().shard(1000) == oat.shard(1000)));
+        }
+
+        /// Test that routing to no shard at all panics.
+        #[test]
+        #[should_panic]
+        fn test_no_shards() {
+            Oat::of(1, 3, 0).shard(0);
+        }
+
+        /// Test calendar partitions around month, year and leap day boundaries.
+        #[test]
+        fn test_partition() {
+            let day = TimePartitioner::of(Granularity::Day);
+            let month = TimePartitioner::of(Granularity::Month);
+            let year = TimePartitioner::of(Granularity::Year);
+
+            for (millis, expected) in [
+                (0, ["1970-01-01", "1970-01", "1970"]),
+                (1_671_800_400_000, ["2022-12-23", "2022-12", "2022"]),
+                (1_672_531_199_999, ["2022-12-31", "2022-12", "2022"]),
+                (1_672_531_200_000, ["2023-01-01", "2023-01", "2023"]),
+                (1_709_164_800_000, ["2024-02-29", "2024-02", "2024"]),
+                (4_107_542_400_000, ["2100-03-01", "2100-03", "2100"]),
+            ] {
+                let oat = Oat::of(1, 3, millis);
+                let partitions = [day, month, year].map(|partitioner| partitioner.partition(&oat));
+
+                assert_eq!(partitions.map(|partition| partition.to_string()), expected);
+                assert!(partitions
+                    .iter()
+                    .all(|partition| partition.start_unix_millis() <= millis as u128));
+            }
+        }
+
+        /// Test that the partitioner honours the epoch and the time unit of the layout.
+        #[test]
+        fn test_partition_epoch_and_layout() {
+            let layout = Layout::STANDARD.with_unit(TimeUnit::Seconds);
+            let partitioner = TimePartitioner::of(Granularity::Day)
+                .with_epoch(Epoch::OATS)
+                .with_layout(layout);
+
+            let oat = Oat::with_layout(&layout, 1, 3, 59 * 24 * 60 * 60);
+            let partition = partitioner.partition(&oat);
+
+            assert_eq!(
+                (partition.year(), partition.month(), partition.day()),
+                (2023, 3, 1)
+            );
+            assert_eq!(partition.start_unix_millis(), 1_677_628_800_000);
+            assert!(partition > partitioner.partition(&Oat::with_layout(&layout, 1, 3, 0)));
+        }
+
+        /// Test that periods of a layout with 64 bits of seconds start beyond the range of a `u64`.
+        #[test]
+        fn test_partition_wide_seconds() {
+            let layout = Layout::new(4, 64, 4, TimeUnit::Seconds);
+            let partitioner = TimePartitioner::of(Granularity::Day).with_layout(layout);
+
+            let oat = Oat::with_layout(&layout, 1, 3, u64::MAX);
+            let start = partitioner.partition(&oat).start_unix_millis();
+            let millis = u64::MAX as u128 * 1000;
+
+            assert!(start > u64::MAX as u128);
+            assert!(start <= millis && millis - start < 24 * 60 * 60 * 1000);
+        }
+    }
+
+    /// The `signed` module contains tests for the `SignedOat` and the `OatSigner`.
+    #[cfg(feature = "signed")]
+    mod signed {
+        use crate::oat::{Oat, ParseOatError};
+        use crate::signed::{OatSigner, SignatureError, ENCODED_LEN};
+
+        /// Test tokens against tags computed independently with Python's `hmac` module.
+        #[test]
+        fn test_vectors() {
+            let oat = Oat::of(1, 3, 1_671_800_400_000);
+
+            for (signer, token) in [
+                (
+                    OatSigner::of(1, b"secret"),
+                    "X1AwCIGvFTGAA.01OGBBSb6I-WpoQkhW",
+                ),
+                (
+                    OatSigner::of(0xA7, &[0; 32]),
+                    "X1AwCIGvFTGAA.A72Em6bymxixxCs7Ro",
+                ),
+            ] {
+                let signed = signer.sign(&oat);
+
+                assert_eq!(signed.to_string(), token);
+                assert_eq!(token.len(), ENCODED_LEN);
+                assert_eq!(signer.verify(token), Ok(signed));
+            }
+        }
+
+        /// Test that tokens signed with a rotated-out key keep verifying until the key is dropped.
+        #[test]
+        fn test_key_rotation() {
+            let oat = Oat::of(7, 1, 1_671_800_400_000);
+            let old = OatSigner::of(1, b"old secret");
+            let token = old.sign(&oat).to_string();
+
+            let rotated = OatSigner::of(2, b"new secret").with_key(1, b"old secret");
+            assert_eq!(rotated.verify(&token).unwrap().key_id(), 1);
+            assert_eq!(rotated.sign(&oat).key_id(), 2);
+            assert!(old.verify(&rotated.sign(&oat).to_string()).is_err());
+
+            let dropped = OatSigner::of(2, b"new secret");
+            assert_eq!(dropped.verify(&token), Err(SignatureError::UnknownKey(1)));
+
+            let replaced = rotated.with_key(1, b"leaked secret");
+            assert_eq!(replaced.verify(&token), Err(SignatureError::InvalidTag));
+        }
+
+        /// Test that malformed and tampered tokens are rejected.
+        #[test]
+        fn test_verify_invalid() {
+            let signer = OatSigner::of(1, b"secret");
+            let token = "X1AwCIGvFTGAA.01OGBBSb6I-WpoQkhW";
+
+            assert_eq!(
+                signer.verify("X1AwCIGvFTGAA"),
+                Err(SignatureError::InvalidLength(13))
+            );
+            assert_eq!(
+                signer.verify(&token.replace('.', "~")),
+                Err(SignatureError::InvalidCharacter(13))
+            );
+            assert_eq!(
+                signer.verify("X1AwCIGvFTGAA.0aOGBBSb6I-WpoQkhW"),
+                Err(SignatureError::InvalidCharacter(15))
+            );
+            assert_eq!(
+                signer.verify("01AwCIGvFTGAA.01OGBBSb6I-WpoQkhW"),
+                Err(SignatureError::InvalidOat(ParseOatError::InvalidCharacter(
+                    0
+                )))
+            );
+            assert_eq!(
+                signer.verify("X1AwCIGvFTGAA.01OGBBSb6I-WpoQkh+"),
+                Err(SignatureError::InvalidTag)
+            );
+
+            // Every single changed character of the Oat or the tag invalidates the token.
+            for index in (0..13).chain(16..32) {
+                let mut tampered = token.as_bytes().to_vec();
+                tampered[index] = if tampered[index] == b'A' { b'B' } else { b'A' };
+                let tampered = String::from_utf8(tampered).unwrap();
+
+                assert!(signer.verify(&tampered).is_err(), "{tampered}");
+            }
+        }
+
+        /// Test that the keys do not show up in debug output.
+        #[test]
+        fn test_debug() {
+            let signer = OatSigner::of(2, b"secret").with_key(1, b"old secret");
+
+            assert_eq!(
+                format!("{signer:?}"),
+                "OatSigner { current: 2, key_ids: [2, 1], .. }"
+            );
+        }
+    }
+
+    /// The `siphash` module contains tests for SipHash-2-4.
+    mod siphash {
+        use crate::siphash::siphash24;
+
+        /// Test the reference vectors of SipHash-2-4 for the key `00 01 02 .. 0f`.
+        #[test]
+        fn test_siphash24() {
+            let key = core::array::from_fn(|i| i as u8);
+            let data: [u8; 64] = core::array::from_fn(|i| i as u8);
+
+            assert_eq!(siphash24(&key, &data[..0]), 0x726f_db47_dd0e_0e31);
+            assert_eq!(siphash24(&key, &data[..1]), 0x74f8_39c5_93dc_67fd);
+            assert_eq!(siphash24(&key, &data[..8]), 0x93f5_f579_9a93_2462);
+            assert_eq!(siphash24(&key, &data[..15]), 0xa129_ca61_49be_45e5);
         }
-        
     }
 }
@@ -0,0 +1,189 @@
+use crate::bowl::WrappedBowl;
+use crate::observer::{BowlStats, WAIT_BUCKETS};
+use std::fmt::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The `Content-Type` of the Prometheus text exposition format, version 0.0.4.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// A set of bowls whose statistics are exposed to Prometheus.
+///
+/// Every bowl is labelled with its node and the Unix milliseconds of its epoch, so registered
+/// bowls should not share both. Epochs before the Unix epoch are labelled as an empty string.
+/// The registry keeps the bowls alive, and clones of the registry share the same bowls, so it
+/// can be handed to the thread serving `/metrics`.
+///
+/// # Examples
+///
+/// ```
+/// use oats::bowl::{BowlConfig, WrappedBowl};
+/// use oats::epoch::Epoch;
+/// use oats::metrics::BowlRegistry;
+///
+/// let wrapped_bowl = WrappedBowl::from_config(BowlConfig::of(1).with_epoch(Epoch::OATS)).unwrap();
+/// let registry = BowlRegistry::new();
+/// registry.register(&wrapped_bowl);
+///
+/// wrapped_bowl.generate();
+///
+/// let metrics = registry.render();
+/// assert!(metrics.contains("oats_ids_issued_total{node=\"1\",epoch=\"1672531200000\"} 1\n"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BowlRegistry {
+    bowls: Arc<Mutex<Vec<WrappedBowl>>>,
+}
+
+/// The values of a single bowl, read once per scrape.
+struct Sample {
+    labels: String,
+    stats: BowlStats,
+    drift: Duration,
+}
+
+impl BowlRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        BowlRegistry::default()
+    }
+
+    /// Adds the bowl to the registry, unless it is registered already.
+    pub fn register(&self, bowl: &WrappedBowl) {
+        let mut bowls = self.bowls.lock().expect("Failed to get lock.");
+        if !bowls.iter().any(|registered| registered.same_bowl(bowl)) {
+            bowls.push(bowl.clone());
+        }
+    }
+
+    /// Removes the bowl from the registry.
+    pub fn unregister(&self, bowl: &WrappedBowl) {
+        let mut bowls = self.bowls.lock().expect("Failed to get lock.");
+        bowls.retain(|registered| !registered.same_bowl(bowl));
+    }
+
+    /// Renders the statistics of all registered bowls in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out)
+            .expect("Writing to a String never fails.");
+        out
+    }
+
+    /// Writes the statistics of all registered bowls in the Prometheus text format to `out`.
+    ///
+    /// The output contains the following metric families, each bowl labelled with `node` and
+    /// `epoch`:
+    ///
+    /// * `oats_ids_issued_total` - The Oats generated.
+    /// * `oats_sequence_wraps_total` - How often the sequence numbers of a tick ran out.
+    /// * `oats_clock_regressions_total` - How often the clock stepped backwards.
+    /// * `oats_wait_seconds` - A histogram of the waits for the clock.
+    /// * `oats_drift_seconds` - How far the most recent Oat lies ahead of the clock.
+    pub fn write(&self, out: &mut impl Write) -> fmt::Result {
+        let samples: Vec<Sample> = self
+            .bowls
+            .lock()
+            .expect("Failed to get lock.")
+            .iter()
+            .map(|bowl| {
+                let (node, epoch, stats, drift) = bowl.sample();
+                let epoch = epoch.map_or(String::new(), |epoch| epoch.unix_millis().to_string());
+                Sample {
+                    labels: format!("node=\"{node}\",epoch=\"{epoch}\""),
+                    stats,
+                    drift,
+                }
+            })
+            .collect();
+
+        write_family(
+            out,
+            "oats_ids_issued_total",
+            "counter",
+            "Oats generated by the bowl.",
+        )?;
+        for sample in &samples {
+            let generated = sample.stats.generated();
+            writeln!(
+                out,
+                "oats_ids_issued_total{{{}}} {generated}",
+                sample.labels
+            )?;
+        }
+
+        write_family(
+            out,
+            "oats_sequence_wraps_total",
+            "counter",
+            "Times the sequence numbers of a tick ran out.",
+        )?;
+        for sample in &samples {
+            let wraps = sample.stats.wraps();
+            writeln!(
+                out,
+                "oats_sequence_wraps_total{{{}}} {wraps}",
+                sample.labels
+            )?;
+        }
+
+        write_family(
+            out,
+            "oats_clock_regressions_total",
+            "counter",
+            "Times the clock was seen stepping backwards.",
+        )?;
+        for sample in &samples {
+            let regressions = sample.stats.clock_regressions();
+            writeln!(
+                out,
+                "oats_clock_regressions_total{{{}}} {regressions}",
+                sample.labels
+            )?;
+        }
+
+        write_family(
+            out,
+            "oats_wait_seconds",
+            "histogram",
+            "Time spent waiting for the clock to reach the next tick.",
+        )?;
+        for sample in &samples {
+            let labels = &sample.labels;
+            for (bound, count) in WAIT_BUCKETS.iter().zip(sample.stats.wait_buckets()) {
+                let le = bound.as_secs_f64();
+                writeln!(
+                    out,
+                    "oats_wait_seconds_bucket{{{labels},le=\"{le}\"}} {count}"
+                )?;
+            }
+
+            let (count, sum) = (sample.stats.waits(), sample.stats.wait_time().as_secs_f64());
+            writeln!(
+                out,
+                "oats_wait_seconds_bucket{{{labels},le=\"+Inf\"}} {count}"
+            )?;
+            writeln!(out, "oats_wait_seconds_sum{{{labels}}} {sum}")?;
+            writeln!(out, "oats_wait_seconds_count{{{labels}}} {count}")?;
+        }
+
+        write_family(
+            out,
+            "oats_drift_seconds",
+            "gauge",
+            "How far the most recent Oat lies ahead of the clock.",
+        )?;
+        for sample in &samples {
+            let drift = sample.drift.as_secs_f64();
+            writeln!(out, "oats_drift_seconds{{{}}} {drift}", sample.labels)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the `HELP` and `TYPE` lines introducing a metric family.
+fn write_family(out: &mut impl Write, name: &str, kind: &str, help: &str) -> fmt::Result {
+    writeln!(out, "# HELP {name} {help}")?;
+    writeln!(out, "# TYPE {name} {kind}")
+}
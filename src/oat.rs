@@ -1,9 +1,21 @@
+use crate::layout::Layout;
+use crate::siphash::siphash24;
+#[cfg(feature = "alloc")]
+use alloc::{format, string::String};
 use base64::engine::general_purpose::URL_SAFE_NO_PAD;
-use base64::{DecodeError, Engine};
-use std::hash::{Hash, Hasher};
-use std::num::ParseIntError;
+use base64::{DecodeError, DecodeSliceError, Engine};
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::num::ParseIntError;
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+#[cfg(feature = "uuid")]
+use uuid::{Uuid, Variant};
 
 /// A struct that represents an Oat.
+///
+/// Oats order like their 72-bit value: by node first, then by LUID.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub struct Oat {
     /// The node for the Oat.
     node: u8,
@@ -16,9 +28,72 @@ pub enum ParseOatError {
     InvalidNode(ParseIntError),
     InvalidLUIDForm(DecodeError),
     InvalidLUIDLength(usize),
+    /// The string does not have the length of an encoded Oat.
+    InvalidLength(usize),
+    /// The byte at the given index is not valid at its position.
+    InvalidCharacter(usize),
 }
 
+impl fmt::Display for ParseOatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseOatError::InvalidNode(err) => write!(f, "invalid node: {err}"),
+            ParseOatError::InvalidLUIDForm(err) => write!(f, "invalid LUID encoding: {err}"),
+            ParseOatError::InvalidLUIDLength(len) => write!(f, "invalid LUID length: {len}"),
+            ParseOatError::InvalidLength(len) => write!(f, "invalid Oat length: {len}"),
+            ParseOatError::InvalidCharacter(index) => {
+                write!(f, "invalid character at index {index}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ParseOatError {}
+
+/// The reasons a `Uuid` can not be converted back into an Oat.
+#[cfg(feature = "uuid")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UuidError {
+    /// The UUID is not a version 8 UUID.
+    InvalidVersion(usize),
+    /// The UUID does not use the variant defined by RFC 9562.
+    InvalidVariant,
+    /// The bits after the Oat do not match the mapping, the UUID was not derived from an Oat.
+    InvalidPadding,
+}
+
+#[cfg(feature = "uuid")]
+impl fmt::Display for UuidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UuidError::InvalidVersion(version) => {
+                write!(f, "expected a version 8 UUID, got version {version}")
+            }
+            UuidError::InvalidVariant => write!(f, "expected the RFC 9562 UUID variant"),
+            UuidError::InvalidPadding => write!(f, "UUID was not derived from an Oat"),
+        }
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl core::error::Error for UuidError {}
+
 impl Oat {
+    /// The length of the string representation of an Oat.
+    pub const ENCODED_LEN: usize = 13;
+
+    /// The nil Oat with all bits cleared, for use as a sentinel.
+    pub const NIL: Oat = Oat::from_raw(0);
+
+    /// The smallest Oat, equal to `NIL`.
+    pub const MIN: Oat = Oat::from_raw(0);
+
+    /// The largest Oat, with all 72 bits set.
+    ///
+    /// It sorts after every Oat of any layout, but is not valid for the standard layout, which
+    /// leaves the 8 bits between node and timestamp unused.
+    pub const MAX: Oat = Oat::from_raw((1 << Layout::BITS) - 1);
+
     /// Creates a new Oat with the given node, sequence number, and timestamp.
     ///
     /// # Arguments
@@ -38,25 +113,179 @@ impl Oat {
     /// use oats::oat::Oat;
     ///
     /// let oat = Oat::of(1, 0xfff, 0xfffffffffff);
+    ///
+    /// // Being a `const fn`, it also declares well-known ids.
+    /// const SYSTEM: Oat = Oat::of(0, 1, 0);
     /// ```
-    pub fn of(node: u8, seq: u16, timestamp: u64) -> Self {
-        assert!(seq < (1 << 12));
-        assert!(timestamp < (1 << 44));
+    pub const fn of(node: u8, seq: u16, timestamp: u64) -> Self {
+        Oat::with_layout(&Layout::STANDARD, node as u16, seq, timestamp)
+    }
 
-        let luid = (timestamp << 12) | seq as u64;
+    /// Creates a new Oat with the given node, sequence number, and timestamp, packed according to `layout`.
+    ///
+    /// # Arguments
+    ///
+    /// * `layout` - The layout describing the width of each field.
+    /// * `node` - The node identifier.
+    /// * `seq` - The sequence number.
+    /// * `timestamp` - The timestamp.
+    ///
+    /// # Assertions
+    ///
+    /// Each field must fit into the number of bits the layout reserves for it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::layout::{Layout, TimeUnit};
+    /// use oats::oat::Oat;
+    ///
+    /// let layout = Layout::new(10, 42, 14, TimeUnit::Millis);
+    /// let oat = Oat::with_layout(&layout, 1000, 0x3fff, 1671800400_000);
+    ///
+    /// assert_eq!(oat.node_in(&layout), 1000);
+    /// assert_eq!(oat.seq_in(&layout), 0x3fff);
+    /// assert_eq!(oat.timestamp_in(&layout), 1671800400_000);
+    /// ```
+    pub const fn with_layout(layout: &Layout, node: u16, seq: u16, timestamp: u64) -> Self {
+        assert!(node <= layout.max_node());
+        assert!(seq <= layout.max_seq());
+        assert!(timestamp <= layout.max_timestamp());
+
+        Oat::from_raw(layout.pack(node, seq, timestamp))
+    }
+
+    /// Creates an Oat from its 72-bit value, the node byte being the most significant.
+    pub(crate) const fn from_raw(raw: u128) -> Self {
+        Oat {
+            node: (raw >> 64) as u8,
+            luid: raw as u64,
+        }
+    }
+
+    /// Returns the 72-bit value of the Oat, the node byte being the most significant.
+    pub(crate) const fn raw(&self) -> u128 {
+        (self.node as u128) << 64 | self.luid as u128
+    }
 
-        Oat { node, luid }
+    pub const fn to_bytes(&self) -> [u8; 9] {
+        let luid = self.luid.to_le_bytes();
+        [
+            self.node, luid[0], luid[1], luid[2], luid[3], luid[4], luid[5], luid[6], luid[7],
+        ]
     }
 
-    pub fn to_bytes(&self) -> [u8; 9] {
-        let mut bytes = [0; 9];
-        bytes[0] = self.node;
-        bytes[1..].copy_from_slice(&self.luid.to_le_bytes());
-        bytes
+    /// Writes the string representation of the Oat into `buffer`, without allocating.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The buffer receiving the string.
+    ///
+    /// # Returns
+    ///
+    /// The string representation, borrowed from `buffer`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::oat::Oat;
+    ///
+    /// let oat = Oat::of(1, 3, 1671800400_000);
+    /// let mut buffer = [0; Oat::ENCODED_LEN];
+    ///
+    /// assert_eq!(oat.encode_to(&mut buffer), "X1AwCIGvFTGAA");
+    /// ```
+    pub fn encode_to<'a>(&self, buffer: &'a mut [u8; Oat::ENCODED_LEN]) -> &'a str {
+        const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+        buffer[0] = match self.node >> 4 {
+            0 => b'X',
+            high => HEX[high as usize],
+        };
+        buffer[1] = HEX[(self.node & 0xf) as usize];
+        URL_SAFE_NO_PAD
+            .encode_slice(self.luid.to_le_bytes(), &mut buffer[2..])
+            .expect("Eight bytes always encode to eleven characters.");
+
+        core::str::from_utf8(buffer).expect("The encoding is always ASCII.")
+    }
+
+    /// Writes the string representation of the Oat to `writer`, without allocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fmt::Write;
+    /// use oats::oat::Oat;
+    ///
+    /// let mut line = String::from("id=");
+    /// Oat::of(1, 3, 1671800400_000).write_to(&mut line).unwrap();
+    ///
+    /// assert_eq!(line, "id=X1AwCIGvFTGAA");
+    /// ```
+    pub fn write_to<W: fmt::Write>(&self, writer: &mut W) -> fmt::Result {
+        writer.write_str(self.encode_to(&mut [0; Oat::ENCODED_LEN]))
+    }
+
+    /// Decodes the string representation of an Oat from its bytes, without allocating.
+    ///
+    /// Unlike `from_string`, this is a `const fn`, so it also works in constants.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The ASCII bytes of the string representation.
+    ///
+    /// # Returns
+    ///
+    /// The decoded Oat, or a `ParseOatError` pointing at the first invalid byte.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::oat::{Oat, ParseOatError};
+    ///
+    /// const SYSTEM: Result<Oat, ParseOatError> = Oat::from_ascii(b"X1AwCIGvFTGAA");
+    ///
+    /// assert_eq!(SYSTEM, Ok(Oat::of(1, 3, 1671800400_000)));
+    /// assert_eq!(Oat::from_ascii(b"X1AwCIGv.TGAA"), Err(ParseOatError::InvalidCharacter(8)));
+    /// ```
+    pub const fn from_ascii(bytes: &[u8]) -> Result<Self, ParseOatError> {
+        if bytes.len() != Oat::ENCODED_LEN {
+            return Err(ParseOatError::InvalidLength(bytes.len()));
+        }
+
+        let node = match parse_canonical_node(bytes[0], bytes[1]) {
+            Ok(node) => node,
+            Err(err) => return Err(err),
+        };
+
+        // Eleven characters carry 66 bits, the last two of which must be zero.
+        let mut bits: u128 = 0;
+        let mut index = 2;
+        while index < Oat::ENCODED_LEN {
+            match base64_value(bytes[index]) {
+                Some(value) => bits = bits << 6 | value as u128,
+                None => return Err(ParseOatError::InvalidCharacter(index)),
+            }
+            index += 1;
+        }
+        if bits & 0b11 != 0 {
+            return Err(ParseOatError::InvalidCharacter(Oat::ENCODED_LEN - 1));
+        }
+
+        Ok(Oat {
+            node,
+            // The bytes were encoded little endian, but read in big endian order.
+            luid: ((bits >> 2) as u64).swap_bytes(),
+        })
     }
 
     /// Parses a string representation of an Oat and returns a new Oat instance.
     ///
+    /// Only the canonical node encoding is accepted: nodes below 16 are written as `X` and one
+    /// digit, all others as two digits, always in upper case. Use `from_string_lenient` for
+    /// strings written by other encoders.
+    ///
     /// # Arguments
     ///
     /// * `string` - The string representation of the Oat.
@@ -68,28 +297,57 @@ impl Oat {
     /// # Examples
     ///
     /// ```
-    /// use oats::oat::Oat;
+    /// use oats::oat::{Oat, ParseOatError};
     ///
     /// let oat = Oat::from_string("X1AwCIGvFTGAA").expect("Failed to parse Oat.");
     /// assert_eq!(oat.node(), 1);
     /// assert_eq!(oat.seq(), 3);
     /// assert_eq!(oat.timestamp(), 1671800400_000);
+    ///
+    /// assert_eq!(Oat::from_string("01AwCIGvFTGAA"), Err(ParseOatError::InvalidCharacter(0)));
     /// ```
     pub fn from_string(string: &str) -> Result<Self, ParseOatError> {
-        let node = u8::from_str_radix(&string[0..2].replace('X', "0"), 16)
-            .map_err(ParseOatError::InvalidNode)?;
-        let luid = URL_SAFE_NO_PAD.decode(&string[2..]).map_err(ParseOatError::InvalidLUIDForm)?;
-
-        if luid.len() != 8 {
-            return Err(ParseOatError::InvalidLUIDLength(luid.len()));
+        let bytes = string.as_bytes();
+        if bytes.len() < 2 {
+            return Err(ParseOatError::InvalidLength(bytes.len()));
         }
 
-        let mut luid_bytes = [0; 8];
-        luid_bytes.copy_from_slice(&luid);
+        Ok(Oat {
+            node: parse_canonical_node(bytes[0], bytes[1])?,
+            // Both node characters are ASCII, so the LUID starts at a character boundary.
+            luid: decode_luid(&string[2..])?,
+        })
+    }
+
+    /// Parses a string representation of an Oat, accepting the node encodings of older versions.
+    ///
+    /// Every `X` in the node stands for a zero and both `X` and the hex digits may be in either
+    /// case, so `01`, `x1` and `X1` all denote node 1. Note that this makes `1X` node 16.
+    ///
+    /// # Arguments
+    ///
+    /// * `string` - The string representation of the Oat.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parsed Oat if successful, or a `ParseOatError` if parsing fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::oat::Oat;
+    ///
+    /// let oat = Oat::from_string_lenient("01AwCIGvFTGAA").expect("Failed to parse Oat.");
+    /// assert_eq!(oat, Oat::from_string("X1AwCIGvFTGAA").unwrap());
+    /// ```
+    pub fn from_string_lenient(string: &str) -> Result<Self, ParseOatError> {
+        let (Some(node), Some(luid)) = (string.get(0..2), string.get(2..)) else {
+            return Err(ParseOatError::InvalidLength(string.len()));
+        };
 
         Ok(Oat {
-            node,
-            luid: u64::from_le_bytes(luid_bytes),
+            node: parse_node(node).map_err(ParseOatError::InvalidNode)?,
+            luid: decode_luid(luid)?,
         })
     }
 
@@ -108,15 +366,9 @@ impl Oat {
     /// This function assumes that the input string is a valid representation of an Oat and does not perform any validation or error handling.
     /// Incorrect input may lead to undefined behavior.
     pub fn from_string_unchecked(string: &str) -> Self {
-        let node = u8::from_str_radix(&string[0..2].replace('X', "0"), 16).unwrap();
-        let luid = URL_SAFE_NO_PAD.decode(&string[2..]).unwrap();
-
-        let mut luid_bytes = [0; 8];
-        luid_bytes.copy_from_slice(&luid);
-
         Oat {
-            node,
-            luid: u64::from_le_bytes(luid_bytes),
+            node: parse_node(&string[0..2]).unwrap(),
+            luid: decode_luid(&string[2..]).unwrap(),
         }
     }
 
@@ -142,10 +394,11 @@ impl Oat {
     /// let bytes = [1, 0x03, 0x20, 0x88, 0x1A, 0xF1, 0x53, 0x18, 0x20];
     /// let oat = Oat::from_bytes(bytes);
     /// ```
-    pub fn from_bytes(bytes: [u8; 9]) -> Self {
+    pub const fn from_bytes(bytes: [u8; 9]) -> Self {
+        let [node, luid @ ..] = bytes;
         Oat {
-            node: bytes[0],
-            luid: u64::from_le_bytes(bytes[1..].try_into().unwrap()),
+            node,
+            luid: u64::from_le_bytes(luid),
         }
     }
 
@@ -159,6 +412,241 @@ impl Oat {
             luid: u64::from_le_bytes(bytes[1..].try_into().unwrap()),
         })
     }
+
+    /// Returns the 72-bit value of the Oat as an integer, the node byte being the most significant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::oat::Oat;
+    ///
+    /// let oat = Oat::of(1, 3, 1671800400_000);
+    /// assert_eq!(oat.to_u128(), 0x01_0018_53f1_1a88_0003);
+    /// ```
+    pub const fn to_u128(&self) -> u128 {
+        self.raw()
+    }
+
+    /// Creates an Oat from the integer returned by `to_u128`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The 72-bit value of the Oat.
+    ///
+    /// # Returns
+    ///
+    /// The Oat, or `None` if any bit above the lowest 72 is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::oat::Oat;
+    ///
+    /// let oat = Oat::of(1, 3, 1671800400_000);
+    /// assert_eq!(Oat::from_u128(oat.to_u128()), Some(oat));
+    /// assert_eq!(Oat::from_u128(1 << 72), None);
+    /// ```
+    pub const fn from_u128(value: u128) -> Option<Self> {
+        if value >> Layout::BITS != 0 {
+            return None;
+        }
+
+        Some(Oat::from_raw(value))
+    }
+}
+
+/// Creates an Oat from its string representation at compile time.
+///
+/// The literal is parsed with `Oat::from_ascii` in a constant, so an invalid literal fails the
+/// build instead of panicking at runtime.
+///
+/// # Examples
+///
+/// ```
+/// use oats::oat;
+/// use oats::oat::Oat;
+///
+/// const SYSTEM_USER: Oat = oat!("X1AwCIGvFTGAA");
+///
+/// assert_eq!(SYSTEM_USER, Oat::of(1, 3, 1671800400_000));
+/// ```
+///
+/// ```compile_fail
+/// const BROKEN: oats::oat::Oat = oats::oat!("X1AwCIGv.TGAA");
+/// ```
+#[macro_export]
+macro_rules! oat {
+    ($literal:literal) => {{
+        const OAT: $crate::oat::Oat = match $crate::oat::Oat::from_ascii($literal.as_bytes()) {
+            Ok(oat) => oat,
+            Err(_) => panic!(concat!("Invalid Oat literal: ", $literal)),
+        };
+        OAT
+    }};
+}
+
+/// Returns the value of an upper case ASCII hex digit.
+const fn hex_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Parses the canonical encoding of the node, an `X` and one digit below 16, two digits above.
+const fn parse_canonical_node(high: u8, low: u8) -> Result<u8, ParseOatError> {
+    let Some(low) = hex_value(low) else {
+        return Err(ParseOatError::InvalidCharacter(1));
+    };
+
+    match (high, hex_value(high)) {
+        (b'X', _) => Ok(low),
+        (_, Some(high)) if high != 0 => Ok(high << 4 | low),
+        _ => Err(ParseOatError::InvalidCharacter(0)),
+    }
+}
+
+/// Returns the value of a character of the URL-safe base64 alphabet.
+const fn base64_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'A'..=b'Z' => Some(byte - b'A'),
+        b'a'..=b'z' => Some(byte - b'a' + 26),
+        b'0'..=b'9' => Some(byte - b'0' + 52),
+        b'-' => Some(62),
+        b'_' => Some(63),
+        _ => None,
+    }
+}
+
+/// Parses the two hex digits of the node leniently, where every `X` or `x` stands for a zero.
+fn parse_node(hex: &str) -> Result<u8, ParseIntError> {
+    let mut digits = [0; 2];
+    for (digit, byte) in digits.iter_mut().zip(hex.bytes()) {
+        *digit = if byte.eq_ignore_ascii_case(&b'X') {
+            b'0'
+        } else {
+            byte
+        };
+    }
+
+    // Swapping ASCII bytes for ASCII bytes keeps the string valid UTF-8.
+    u8::from_str_radix(core::str::from_utf8(&digits[..hex.len()]).unwrap(), 16)
+}
+
+/// Decodes the base64 encoded little endian LUID.
+fn decode_luid(encoded: &str) -> Result<u64, ParseOatError> {
+    // Leave some room, so a slightly too long LUID is reported with its length.
+    let mut luid = [0; 12];
+
+    match URL_SAFE_NO_PAD.decode_slice(encoded, &mut luid) {
+        Ok(8) => Ok(u64::from_le_bytes(luid[..8].try_into().unwrap())),
+        Ok(len) => Err(ParseOatError::InvalidLUIDLength(len)),
+        Err(DecodeSliceError::DecodeError(err)) => Err(ParseOatError::InvalidLUIDForm(err)),
+        Err(DecodeSliceError::OutputSliceTooSmall) => {
+            Err(ParseOatError::InvalidLUIDLength(encoded.len() * 3 / 4))
+        }
+    }
+}
+
+/// Marks UUIDs created by `Oat::to_uuid_ordered`, so they are not mistaken for `Oat::to_uuid`.
+#[cfg(feature = "uuid")]
+const UUID_ORDERED_TAG: u128 = 1 << 49;
+
+#[cfg(feature = "uuid")]
+impl Oat {
+    /// Converts the Oat into a version 8 UUID, keeping the node byte first.
+    ///
+    /// The 72 bits of the Oat fill the custom fields of the UUID from the most significant bit
+    /// on, skipping the version and variant bits. The remaining bits are zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::oat::Oat;
+    ///
+    /// let oat = Oat::of(1, 3, 1671800400_000);
+    /// let uuid = oat.to_uuid();
+    ///
+    /// assert_eq!(uuid.get_version_num(), 8);
+    /// assert_eq!(Oat::from_uuid(uuid), Ok(oat));
+    /// ```
+    pub fn to_uuid(&self) -> Uuid {
+        Uuid::from_u128(pack_uuid(self.raw(), 0))
+    }
+
+    /// Converts a UUID created by `to_uuid` back into the Oat.
+    ///
+    /// # Returns
+    ///
+    /// The Oat, or a `UuidError` if the UUID was not created by `to_uuid`.
+    pub fn from_uuid(uuid: Uuid) -> Result<Self, UuidError> {
+        unpack_uuid(uuid, 0).map(Oat::from_raw)
+    }
+
+    /// Converts the Oat into a version 8 UUID that sorts by time like a UUIDv7.
+    ///
+    /// The LUID comes first and the node byte last, so with the standard layout the UUIDs order
+    /// by timestamp, then sequence number, then node, both as bytes and as strings. The UUID
+    /// does not carry a Unix timestamp though, so it is not a UUIDv7.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::oat::Oat;
+    ///
+    /// let earlier = Oat::of(200, 0, 1671800400_000);
+    /// let later = Oat::of(1, 0, 1671800400_001);
+    ///
+    /// assert!(earlier.to_uuid_ordered() < later.to_uuid_ordered());
+    /// assert_eq!(Oat::from_uuid_ordered(later.to_uuid_ordered()), Ok(later));
+    /// ```
+    pub fn to_uuid_ordered(&self) -> Uuid {
+        let key = (self.luid as u128) << 8 | self.node as u128;
+        Uuid::from_u128(pack_uuid(key, UUID_ORDERED_TAG))
+    }
+
+    /// Converts a UUID created by `to_uuid_ordered` back into the Oat.
+    ///
+    /// # Returns
+    ///
+    /// The Oat, or a `UuidError` if the UUID was not created by `to_uuid_ordered`.
+    pub fn from_uuid_ordered(uuid: Uuid) -> Result<Self, UuidError> {
+        unpack_uuid(uuid, UUID_ORDERED_TAG).map(|key| Oat {
+            node: key as u8,
+            luid: (key >> 8) as u64,
+        })
+    }
+}
+
+/// Spreads 72 bits over the custom fields of a UUIDv8, setting version, variant and `tag`.
+#[cfg(feature = "uuid")]
+fn pack_uuid(bits: u128, tag: u128) -> u128 {
+    // custom_a (48 bits) | ver | custom_b (12 bits) | var | custom_c (62 bits)
+    (bits >> 24) << 80
+        | 0x8 << 76
+        | ((bits >> 12) & 0xfff) << 64
+        | 0b10 << 62
+        | (bits & 0xfff) << 50
+        | tag
+}
+
+/// Reverses `pack_uuid`, checking version, variant and the padding bits.
+#[cfg(feature = "uuid")]
+fn unpack_uuid(uuid: Uuid, tag: u128) -> Result<u128, UuidError> {
+    if uuid.get_version_num() != 8 {
+        return Err(UuidError::InvalidVersion(uuid.get_version_num()));
+    }
+    if uuid.get_variant() != Variant::RFC4122 {
+        return Err(UuidError::InvalidVariant);
+    }
+
+    let value = uuid.as_u128();
+    if value & ((1 << 50) - 1) != tag {
+        return Err(UuidError::InvalidPadding);
+    }
+
+    Ok((value >> 80) << 24 | ((value >> 64) & 0xfff) << 12 | (value >> 50) & 0xfff)
 }
 
 impl Oat {
@@ -172,7 +660,7 @@ impl Oat {
     /// let oat = Oat::of(1, 0, 0);
     /// assert_eq!(oat.node(), 1);
     /// ```
-    pub fn node(&self) -> u8 {
+    pub const fn node(&self) -> u8 {
         self.node
     }
 
@@ -186,7 +674,7 @@ impl Oat {
     /// let oat = Oat::of(1, 0xfff, 0);
     /// assert_eq!(oat.seq(), 0xfff);
     /// ```
-    pub fn seq(&self) -> u16 {
+    pub const fn seq(&self) -> u16 {
         (self.luid & 0xfff) as u16
     }
 
@@ -200,15 +688,85 @@ impl Oat {
     /// let oat = Oat::of(1, 0, 0xfffffffffff);
     /// assert_eq!(oat.timestamp(), 0xfffffffffff);
     /// ```
-    pub fn timestamp(&self) -> u64 {
+    pub const fn timestamp(&self) -> u64 {
         self.luid >> 12
     }
 
+    /// Returns the node for the Oat, interpreted according to `layout`.
+    pub const fn node_in(&self, layout: &Layout) -> u16 {
+        layout.node_of(self.raw())
+    }
+
+    /// Returns the sequence number for the Oat, interpreted according to `layout`.
+    pub const fn seq_in(&self, layout: &Layout) -> u16 {
+        layout.seq_of(self.raw())
+    }
+
+    /// Returns the timestamp for the Oat, interpreted according to `layout`.
+    pub const fn timestamp_in(&self, layout: &Layout) -> u64 {
+        layout.timestamp_of(self.raw())
+    }
+
+    /// Returns the instant the Oat was generated at, given the layout and epoch of its bowl.
+    ///
+    /// The result is truncated to the time unit of the layout.
+    ///
+    /// # Returns
+    ///
+    /// The instant, or `None` if it lies beyond what `SystemTime` can represent, which wide
+    /// layouts counting in seconds can reach.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    /// use oats::layout::{Layout, TimeUnit};
+    /// use oats::oat::Oat;
+    ///
+    /// let layout = Layout::STANDARD.with_unit(TimeUnit::Seconds);
+    /// let oat = Oat::with_layout(&layout, 1, 0, 1671800400);
+    ///
+    /// assert_eq!(
+    ///     oat.instant_in(&layout, None),
+    ///     Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1671800400))
+    /// );
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn instant_in(&self, layout: &Layout, epoch: Option<SystemTime>) -> Option<SystemTime> {
+        epoch
+            .unwrap_or(SystemTime::UNIX_EPOCH)
+            .checked_add(layout.unit().to_duration(self.timestamp_in(layout)))
+    }
+
+    /// Hashes the Oat with SipHash-2-4 under the given 128-bit key.
+    ///
+    /// The hash covers all 9 bytes of `to_bytes` and is stable: the same key and Oat give the
+    /// same hash on every platform, with every Rust version and in every version of this crate,
+    /// so it can route Oats to shards or partitions. Pick a random key to keep others from
+    /// steering Oats to a shard of their choice; a fixed key like all zeros is fine otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::oat::Oat;
+    ///
+    /// let key = core::array::from_fn(|i| i as u8);
+    /// let oat = Oat::of(1, 3, 1671800400_000);
+    ///
+    /// assert_eq!(oat.stable_hash(&key), 0x5f02_ce13_ab67_ef21);
+    /// ```
+    pub const fn stable_hash(&self, key: &[u8; 16]) -> u64 {
+        siphash24(key, &self.to_bytes())
+    }
+
     /// Hashes the Oat using the given `Hasher` implementation and returns the result as a `String`.
     ///
     /// WARNING: The provided hash function might not be cryptographically secure.
     /// Because the returned hash is just a u64, collisions are not unlikely (https://en.wikipedia.org/wiki/Birthday_attack).
     ///
+    /// The result depends on the internals of the hasher, which for `RandomState` change with
+    /// every process and may change with every Rust version.
+    ///
     /// # Examples
     ///
     /// ```
@@ -220,6 +778,10 @@ impl Oat {
     /// let hasher = RandomState::new().build_hasher();
     /// let hash = oat.hashed(hasher);
     /// ```
+    #[cfg(feature = "alloc")]
+    #[deprecated(
+        note = "the result depends on the hasher's internals, use `Oat::stable_hash` instead"
+    )]
     pub fn hashed<H: Hasher>(&self, mut new: H) -> String {
         self.hash(&mut new);
         let hash = new.finish();
@@ -227,7 +789,7 @@ impl Oat {
         format!(
             "{:X>2X}{}",
             self.node,
-            URL_SAFE_NO_PAD.encode(&hash.to_le_bytes())
+            URL_SAFE_NO_PAD.encode(hash.to_le_bytes())
         )
     }
 }
@@ -247,13 +809,13 @@ impl Hash for Oat {
     /// oat.hash(&mut hasher);
     /// ```
     fn hash<H: Hasher>(&self, state: &mut H) {
-        // Write the locally unique identifier to the hasher.
-        state.write(&self.luid.to_le_bytes())
+        // Write the node and the locally unique identifier to the hasher.
+        state.write(&self.to_bytes())
     }
 }
 
-impl ToString for Oat {
-    /// Converts the Oat to a string representation.
+impl fmt::Display for Oat {
+    /// Formats the Oat as its string representation.
     ///
     /// # Examples
     ///
@@ -263,18 +825,15 @@ impl ToString for Oat {
     /// let oat = Oat::of(1, 0, 0);
     /// let string = oat.to_string();
     /// ```
-    fn to_string(&self) -> String {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // Format the locally unique identifier and node as a string.
-        format!(
-            "{:X>2X}{}",
-            &self.node,
-            URL_SAFE_NO_PAD.encode(&self.luid.to_le_bytes())
-        )
+        self.write_to(f)
     }
 }
 
 /// Implements the conversion of `Oat` into a `String`.
-impl Into<String> for Oat {
+#[cfg(feature = "alloc")]
+impl From<Oat> for String {
     /// Converts the `Oat` into a `String`.
     ///
     /// # Examples
@@ -286,13 +845,13 @@ impl Into<String> for Oat {
     /// let string: String = oat.into();
     /// assert_eq!(string, "X1AwCIGvFTGAA");
     /// ```
-    fn into(self) -> String {
-        self.to_string()
+    fn from(oat: Oat) -> Self {
+        format!("{oat}")
     }
 }
 
 /// Converts an `Oat` struct into a fixed-size byte array of length 9.
-impl Into<[u8; 9]> for Oat {
+impl From<Oat> for [u8; 9] {
     /// Converts the `Oat` struct into a byte array.
     ///
     /// # Returns
@@ -306,13 +865,44 @@ impl Into<[u8; 9]> for Oat {
     ///
     /// let oat = Oat::of(1, 3, 1671800400_000);
     /// let bytes: [u8; 9] = oat.into();
-    /// 
+    ///
     /// assert_eq!(bytes, [1, 0x03, 0x0, 0x88, 0x1A, 0xF1, 0x53, 0x18, 0x0]);
     /// ```
-    fn into(self) -> [u8; 9] {
-        let mut bytes = [0; 9];
-        bytes[0] = self.node;
-        bytes[1..].copy_from_slice(&self.luid.to_le_bytes());
-        bytes
+    fn from(oat: Oat) -> Self {
+        oat.to_bytes()
+    }
+}
+
+/// Implements the conversion of `Oat` into its 72-bit integer value.
+impl From<Oat> for u128 {
+    fn from(oat: Oat) -> Self {
+        oat.to_u128()
+    }
+}
+
+/// Implements the conversion of a 72-bit integer into an `Oat`, handing back larger values.
+impl TryFrom<u128> for Oat {
+    type Error = u128;
+
+    fn try_from(value: u128) -> Result<Self, Self::Error> {
+        Oat::from_u128(value).ok_or(value)
+    }
+}
+
+/// Implements the conversion of `Oat` into a version 8 UUID, see `Oat::to_uuid`.
+#[cfg(feature = "uuid")]
+impl From<Oat> for Uuid {
+    fn from(oat: Oat) -> Self {
+        oat.to_uuid()
+    }
+}
+
+/// Implements the conversion of a UUID created by `Oat::to_uuid` back into an `Oat`.
+#[cfg(feature = "uuid")]
+impl TryFrom<Uuid> for Oat {
+    type Error = UuidError;
+
+    fn try_from(uuid: Uuid) -> Result<Self, Self::Error> {
+        Oat::from_uuid(uuid)
     }
 }
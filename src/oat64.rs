@@ -0,0 +1,210 @@
+use crate::layout::Layout;
+use crate::oat::Oat;
+use core::fmt;
+
+/// A struct that represents an Oat packed into a single `u64`.
+///
+/// The fields are packed like in an Oat, the node in front of the timestamp and the sequence
+/// number, but without the unused bits in between. The layout therefore has to fit into 64 bits:
+/// the `STANDARD` layout does exactly, while `Layout::JS_SAFE` stays below 2^53 so the value
+/// survives a round trip through a JavaScript number.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct Oat64(u64);
+
+/// The reasons an Oat can not be packed into an `Oat64`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Oat64Error {
+    /// The layout needs more than 64 bits.
+    TooWide(u32),
+    /// The Oat has bits set outside the fields of the layout.
+    NotInLayout,
+}
+
+impl Oat64 {
+    /// Creates a new Oat64 with the given node, sequence number, and timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node identifier.
+    /// * `seq` - The sequence number.
+    /// * `timestamp` - The timestamp.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::oat64::Oat64;
+    ///
+    /// let oat = Oat64::of(1, 3, 1671800400_000);
+    /// assert_eq!(oat.to_u64(), 0x0118_53f1_1a88_0003);
+    /// ```
+    pub const fn of(node: u8, seq: u16, timestamp: u64) -> Self {
+        Oat64::with_layout(&Layout::STANDARD, node as u16, seq, timestamp)
+    }
+
+    /// Creates a new Oat64 with the given node, sequence number, and timestamp, packed according to `layout`.
+    ///
+    /// # Assertions
+    ///
+    /// The layout must fit into 64 bits and each field into the number of bits the layout reserves for it.
+    /// When used to initialize a constant, a violation is a compile-time error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::layout::Layout;
+    /// use oats::oat64::Oat64;
+    ///
+    /// let oat = Oat64::with_layout(&Layout::JS_SAFE, 255, 15, (1 << 41) - 1);
+    /// assert_eq!(oat.to_u64(), (1 << 53) - 1);
+    /// ```
+    pub const fn with_layout(layout: &Layout, node: u16, seq: u16, timestamp: u64) -> Self {
+        assert!(
+            layout.total_bits() <= 64,
+            "Layout does not fit into 64 bits."
+        );
+        assert!(node <= layout.max_node());
+        assert!(seq <= layout.max_seq());
+        assert!(timestamp <= layout.max_timestamp());
+
+        let fields = (layout.timestamp_bits() + layout.seq_bits()) as u32;
+        Oat64(((node as u64) << fields) | (timestamp << layout.seq_bits()) | seq as u64)
+    }
+
+    /// Creates an Oat64 from its integer value.
+    pub const fn from_u64(value: u64) -> Self {
+        Oat64(value)
+    }
+
+    /// Returns the integer value of the Oat64.
+    pub const fn to_u64(&self) -> u64 {
+        self.0
+    }
+
+    /// Packs an Oat generated with `layout` into an Oat64.
+    ///
+    /// # Returns
+    ///
+    /// The Oat64, or an `Oat64Error` if the layout needs more than 64 bits or the Oat does not
+    /// match the layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::layout::Layout;
+    /// use oats::oat::Oat;
+    /// use oats::oat64::Oat64;
+    ///
+    /// let oat = Oat::of(1, 3, 1671800400_000);
+    /// let oat64 = Oat64::from_oat(&oat, &Layout::STANDARD).unwrap();
+    ///
+    /// assert_eq!(oat64.to_oat(&Layout::STANDARD), oat);
+    /// ```
+    pub const fn from_oat(oat: &Oat, layout: &Layout) -> Result<Self, Oat64Error> {
+        if layout.total_bits() > 64 {
+            return Err(Oat64Error::TooWide(layout.total_bits()));
+        }
+
+        let node = oat.node_in(layout);
+        let seq = oat.seq_in(layout);
+        let timestamp = oat.timestamp_in(layout);
+        if Oat::with_layout(layout, node, seq, timestamp).raw() != oat.raw() {
+            return Err(Oat64Error::NotInLayout);
+        }
+
+        Ok(Oat64::with_layout(layout, node, seq, timestamp))
+    }
+
+    /// Unpacks the Oat64 into the Oat with the same fields, given the layout it was packed with.
+    pub const fn to_oat(&self, layout: &Layout) -> Oat {
+        Oat::with_layout(
+            layout,
+            self.node_in(layout),
+            self.seq_in(layout),
+            self.timestamp_in(layout),
+        )
+    }
+}
+
+impl Oat64 {
+    /// Returns the node for the Oat64.
+    pub const fn node(&self) -> u8 {
+        self.node_in(&Layout::STANDARD) as u8
+    }
+
+    /// Returns the sequence number for the Oat64.
+    pub const fn seq(&self) -> u16 {
+        self.seq_in(&Layout::STANDARD)
+    }
+
+    /// Returns the timestamp for the Oat64.
+    pub const fn timestamp(&self) -> u64 {
+        self.timestamp_in(&Layout::STANDARD)
+    }
+
+    /// Returns the node for the Oat64, interpreted according to `layout`.
+    pub const fn node_in(&self, layout: &Layout) -> u16 {
+        let fields = (layout.timestamp_bits() + layout.seq_bits()) as u32;
+        match self.0.checked_shr(fields) {
+            Some(node) => node as u16 & layout.max_node(),
+            None => 0,
+        }
+    }
+
+    /// Returns the sequence number for the Oat64, interpreted according to `layout`.
+    pub const fn seq_in(&self, layout: &Layout) -> u16 {
+        self.0 as u16 & layout.max_seq()
+    }
+
+    /// Returns the timestamp for the Oat64, interpreted according to `layout`.
+    pub const fn timestamp_in(&self, layout: &Layout) -> u64 {
+        (self.0 >> layout.seq_bits()) & layout.max_timestamp()
+    }
+}
+
+impl fmt::Display for Oat64 {
+    /// Formats the Oat64 as its decimal integer value.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for Oat64Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Oat64Error::TooWide(bits) => write!(f, "layout needs {bits} bits, only 64 fit"),
+            Oat64Error::NotInLayout => write!(f, "Oat does not match the layout"),
+        }
+    }
+}
+
+impl core::error::Error for Oat64Error {}
+
+/// Implements the lossless conversion of a standard `Oat64` into an `Oat`.
+impl From<Oat64> for Oat {
+    fn from(oat: Oat64) -> Self {
+        oat.to_oat(&Layout::STANDARD)
+    }
+}
+
+/// Implements the conversion of a standard `Oat` into an `Oat64`, failing if it has bits set between node and timestamp.
+impl TryFrom<Oat> for Oat64 {
+    type Error = Oat64Error;
+
+    fn try_from(oat: Oat) -> Result<Self, Self::Error> {
+        Oat64::from_oat(&oat, &Layout::STANDARD)
+    }
+}
+
+/// Implements the conversion of `Oat64` into its integer value.
+impl From<Oat64> for u64 {
+    fn from(oat: Oat64) -> Self {
+        oat.to_u64()
+    }
+}
+
+/// Implements the conversion of an integer into an `Oat64`.
+impl From<u64> for Oat64 {
+    fn from(value: u64) -> Self {
+        Oat64::from_u64(value)
+    }
+}
@@ -0,0 +1,190 @@
+use crate::oat::Oat;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// The upper bounds of the buckets `BowlStats::wait_buckets` sorts waits into.
+pub const WAIT_BUCKETS: [Duration; 7] = [
+    Duration::from_micros(10),
+    Duration::from_micros(100),
+    Duration::from_micros(500),
+    Duration::from_millis(1),
+    Duration::from_millis(10),
+    Duration::from_millis(100),
+    Duration::from_secs(1),
+];
+
+/// Receives the events of a bowl, for metrics and logging.
+///
+/// All callbacks default to doing nothing, so observers only implement the events they care
+/// about. Except for `generated`, the callbacks run while the bowl is locked: they should return
+/// quickly and must not generate Oats from the same bowl.
+///
+/// # Examples
+///
+/// ```
+/// use oats::bowl::{BowlConfig, WrappedBowl};
+/// use oats::epoch::Epoch;
+/// use oats::observer::BowlObserver;
+/// use std::time::Duration;
+///
+/// struct SlowLog;
+///
+/// impl BowlObserver for SlowLog {
+///     fn waited(&self, waited: Duration) {
+///         if waited > Duration::from_millis(1) {
+///             eprintln!("waited {waited:?} for the next tick");
+///         }
+///     }
+/// }
+///
+/// let wrapped_bowl = WrappedBowl::from_config(BowlConfig::of(1).with_epoch(Epoch::OATS))
+///     .unwrap()
+///     .with_observer(SlowLog);
+///
+/// assert_eq!(wrapped_bowl.generate().node(), 1);
+/// ```
+pub trait BowlObserver: Send + Sync {
+    /// Called with every Oat the bowl generated, after the bowl was unlocked.
+    fn generated(&self, _oat: &Oat) {}
+
+    /// Called when the sequence numbers of the tick `timestamp` ran out and the bowl moved on.
+    ///
+    /// Attempts failing under `SequenceOverflowPolicy::Fail` are not reported.
+    fn sequence_wrapped(&self, _timestamp: u64) {}
+
+    /// Called after the bowl waited for the clock to reach the next tick.
+    fn waited(&self, _waited: Duration) {}
+
+    /// Called when the clock reads earlier than it did for the previous Oat.
+    fn clock_regressed(&self, _behind: Duration) {}
+}
+
+/// A snapshot of the counters every bowl keeps, as returned by `WrappedBowl::stats`.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct BowlStats {
+    generated: u64,
+    wraps: u64,
+    waits: u64,
+    wait_time: Duration,
+    wait_buckets: [u64; WAIT_BUCKETS.len()],
+    clock_regressions: u64,
+}
+
+impl BowlStats {
+    /// Returns the number of Oats generated.
+    pub const fn generated(&self) -> u64 {
+        self.generated
+    }
+
+    /// Returns how often the sequence numbers of a tick ran out and the bowl moved on.
+    pub const fn wraps(&self) -> u64 {
+        self.wraps
+    }
+
+    /// Returns how often the bowl waited for the clock.
+    pub const fn waits(&self) -> u64 {
+        self.waits
+    }
+
+    /// Returns the total time spent waiting for the clock.
+    pub const fn wait_time(&self) -> Duration {
+        self.wait_time
+    }
+
+    /// Returns the number of waits no longer than each of the bounds in `WAIT_BUCKETS`.
+    ///
+    /// The counts are cumulative, like the buckets of a Prometheus histogram: waits within the
+    /// first bound count towards every bucket.
+    pub const fn wait_buckets(&self) -> [u64; WAIT_BUCKETS.len()] {
+        self.wait_buckets
+    }
+
+    /// Returns how often the clock was seen stepping backwards.
+    pub const fn clock_regressions(&self) -> u64 {
+        self.clock_regressions
+    }
+}
+
+/// The counters behind `BowlStats`, shared by all clones of a bowl and readable without locking it.
+#[derive(Debug, Default)]
+pub(crate) struct BowlCounters {
+    generated: AtomicU64,
+    wraps: AtomicU64,
+    waits: AtomicU64,
+    wait_nanos: AtomicU64,
+    wait_buckets: [AtomicU64; WAIT_BUCKETS.len()],
+    clock_regressions: AtomicU64,
+}
+
+impl BowlCounters {
+    pub(crate) fn snapshot(&self) -> BowlStats {
+        let mut wait_buckets = [0; WAIT_BUCKETS.len()];
+        let mut within = 0;
+        for (bucket, count) in wait_buckets.iter_mut().zip(&self.wait_buckets) {
+            within += count.load(Ordering::Relaxed);
+            *bucket = within;
+        }
+
+        BowlStats {
+            generated: self.generated.load(Ordering::Relaxed),
+            wraps: self.wraps.load(Ordering::Relaxed),
+            waits: self.waits.load(Ordering::Relaxed),
+            wait_time: Duration::from_nanos(self.wait_nanos.load(Ordering::Relaxed)),
+            wait_buckets,
+            clock_regressions: self.clock_regressions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl BowlObserver for BowlCounters {
+    fn generated(&self, _oat: &Oat) {
+        self.generated.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn sequence_wrapped(&self, _timestamp: u64) {
+        self.wraps.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn waited(&self, waited: Duration) {
+        let nanos = u64::try_from(waited.as_nanos()).unwrap_or(u64::MAX);
+
+        self.waits.fetch_add(1, Ordering::Relaxed);
+        self.wait_nanos.fetch_add(nanos, Ordering::Relaxed);
+        if let Some(bucket) = WAIT_BUCKETS.iter().position(|&bound| waited <= bound) {
+            self.wait_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn clock_regressed(&self, _behind: Duration) {
+        self.clock_regressions.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Reports the events of a bowl as `tracing` events.
+///
+/// Generated Oats are logged at trace level, wraps and waits at debug level and clock
+/// regressions as warnings. All of them, including `generated`, are emitted within the
+/// `oats.generate` span that `WrappedBowl::try_generate` and `try_generate_oat64` open for the
+/// whole call, which records the node.
+#[cfg(feature = "tracing")]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct TracingObserver;
+
+#[cfg(feature = "tracing")]
+impl BowlObserver for TracingObserver {
+    fn generated(&self, oat: &Oat) {
+        tracing::trace!(%oat, "generated Oat");
+    }
+
+    fn sequence_wrapped(&self, timestamp: u64) {
+        tracing::debug!(timestamp, "sequence numbers ran out");
+    }
+
+    fn waited(&self, waited: Duration) {
+        tracing::debug!(?waited, "waited for the next tick");
+    }
+
+    fn clock_regressed(&self, behind: Duration) {
+        tracing::warn!(?behind, "clock went backwards");
+    }
+}
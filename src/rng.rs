@@ -0,0 +1,57 @@
+#[cfg(feature = "std")]
+use std::{
+    hash::{BuildHasher, RandomState},
+    time::SystemTime,
+};
+
+/// A source of random numbers, used to pick where the sequence numbers of a bowl start.
+///
+/// The numbers only have to be unpredictable enough to hide how many Oats a node generates, so
+/// fast non-cryptographic generators like `SplitMix64` are fine.
+pub trait Rng {
+    /// Returns the next random number.
+    fn next_u64(&mut self) -> u64;
+}
+
+/// The SplitMix64 generator, a tiny seeded generator with a 64-bit state.
+///
+/// The same seed always yields the same numbers, which makes it a good fit for tests.
+///
+/// # Examples
+///
+/// ```
+/// use oats::rng::{Rng, SplitMix64};
+///
+/// let mut rng = SplitMix64::new(1234567);
+///
+/// assert_eq!(rng.next_u64(), 6457827717110365317);
+/// assert_eq!(rng.next_u64(), 3203168211198807973);
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    /// Creates a generator starting from the given seed.
+    pub const fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    /// Creates a generator seeded from the randomly keyed hasher of the standard library and the current time.
+    #[cfg(feature = "std")]
+    pub fn from_entropy() -> Self {
+        SplitMix64::new(RandomState::new().hash_one(SystemTime::now()))
+    }
+}
+
+impl Rng for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
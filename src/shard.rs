@@ -0,0 +1,287 @@
+use crate::epoch::Epoch;
+use crate::layout::Layout;
+use crate::oat::Oat;
+use crate::oat64::Oat64;
+use core::fmt;
+
+/// The key of the stable hash `Oat::shard` routes with.
+const SHARD_KEY: [u8; 16] = [0; 16];
+
+/// The number of milliseconds in a day.
+const MILLIS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+/// Maps a 64-bit key to one of `buckets` buckets with the jump consistent hash of Lamping and Veach.
+///
+/// Keys are spread evenly over the buckets, and growing from `n` to `n + 1` buckets moves only
+/// a `1 / (n + 1)` share of the keys, all of them into the new bucket. The result only depends
+/// on the key and the number of buckets.
+///
+/// # Panics
+///
+/// Panics if `buckets` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use oats::shard::jump_consistent_hash;
+///
+/// assert_eq!(jump_consistent_hash(0, 1), 0);
+/// assert!(jump_consistent_hash(0xdead_beef, 10) < 10);
+/// ```
+pub const fn jump_consistent_hash(mut key: u64, buckets: u32) -> u32 {
+    assert!(buckets > 0, "At least one bucket is needed.");
+
+    let mut bucket: i64 = -1;
+    let mut jump: i64 = 0;
+    while jump < buckets as i64 {
+        bucket = jump;
+        key = key.wrapping_mul(2_862_933_555_777_941_757).wrapping_add(1);
+        jump = ((bucket + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as i64;
+    }
+    bucket as u32
+}
+
+impl Oat {
+    /// Returns the shard out of `shards` the Oat belongs to.
+    ///
+    /// The Oat is hashed with `stable_hash` under a key of zeros, which evens out the highly
+    /// structured node, timestamp and sequence number, and the hash is mapped to a shard with
+    /// `jump_consistent_hash`. The result is stable across platforms and versions of this crate,
+    /// and adding a shard only moves Oats into the new shard.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shards` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::oat::Oat;
+    ///
+    /// let oat = Oat::of(1, 3, 1671800400_000);
+    ///
+    /// assert!(oat.shard(16) < 16);
+    /// assert_eq!(oat.shard(16), oat.shard(16));
+    /// ```
+    pub const fn shard(&self, shards: u32) -> u32 {
+        self.shard_with_key(shards, &SHARD_KEY)
+    }
+
+    /// Returns the shard out of `shards` the Oat belongs to, hashing with a secret key.
+    ///
+    /// Unlike `shard`, others can not steer Oats into a shard of their choice without the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shards` is zero.
+    pub const fn shard_with_key(&self, shards: u32, key: &[u8; 16]) -> u32 {
+        jump_consistent_hash(self.stable_hash(key), shards)
+    }
+}
+
+impl Oat64 {
+    /// Returns the shard out of `shards` the Oat64 belongs to, the same as for the equal standard `Oat`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shards` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::oat::Oat;
+    /// use oats::oat64::Oat64;
+    ///
+    /// let oat = Oat64::of(1, 3, 1671800400_000);
+    ///
+    /// assert_eq!(oat.shard(16), Oat::from(oat).shard(16));
+    /// ```
+    pub fn shard(&self, shards: u32) -> u32 {
+        Oat::from(*self).shard(shards)
+    }
+}
+
+/// The calendar period a `TimePartitioner` groups Oats by.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum Granularity {
+    /// One partition per UTC day.
+    Day,
+    /// One partition per UTC month.
+    Month,
+    /// One partition per UTC year.
+    Year,
+}
+
+/// Maps Oats to the calendar period they were created in, for time-partitioned tables.
+///
+/// The creation time is the timestamp of the Oat counted from the epoch of its bowl, in the
+/// unit of its layout. Periods follow the proleptic Gregorian calendar in UTC.
+///
+/// # Examples
+///
+/// ```
+/// use oats::epoch::Epoch;
+/// use oats::oat::Oat;
+/// use oats::shard::{Granularity, TimePartitioner};
+///
+/// let partitioner = TimePartitioner::of(Granularity::Month).with_epoch(Epoch::OATS);
+/// let oat = Oat::of(1, 3, 40 * 24 * 60 * 60 * 1000);
+///
+/// assert_eq!(partitioner.partition(&oat).to_string(), "2023-02");
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct TimePartitioner {
+    granularity: Granularity,
+    epoch: Epoch,
+    layout: Layout,
+}
+
+/// A calendar period, as returned by `TimePartitioner::partition`.
+///
+/// Periods of the same granularity order chronologically. The month and day of coarser
+/// periods are those of their first day.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct Partition {
+    year: u64,
+    month: u8,
+    day: u8,
+    granularity: Granularity,
+}
+
+impl TimePartitioner {
+    /// Creates a partitioner with the given granularity for standard Oats counted from the Unix epoch.
+    pub const fn of(granularity: Granularity) -> Self {
+        TimePartitioner {
+            granularity,
+            epoch: Epoch::UNIX,
+            layout: Layout::STANDARD,
+        }
+    }
+
+    /// Returns a copy of the partitioner for Oats counted from the given epoch.
+    pub const fn with_epoch(self, epoch: Epoch) -> Self {
+        TimePartitioner { epoch, ..self }
+    }
+
+    /// Returns a copy of the partitioner for Oats generated with the given layout.
+    pub const fn with_layout(self, layout: Layout) -> Self {
+        TimePartitioner { layout, ..self }
+    }
+
+    /// Returns the granularity of the partitions.
+    pub const fn granularity(&self) -> Granularity {
+        self.granularity
+    }
+
+    /// Returns the period the Oat was created in.
+    ///
+    /// Signed Oats, UUIDs and the versioned wire encoding are partitioned through the Oat they
+    /// carry, see `SignedOat::oat`, `Oat::from_uuid` and `Oat::from_bytes_v`.
+    pub const fn partition(&self, oat: &Oat) -> Partition {
+        let ticks = oat.timestamp_in(&self.layout) as u128;
+        let millis =
+            self.epoch.unix_millis() as u128 + ticks * self.layout.unit().as_millis() as u128;
+        let (year, month, day) = civil_from_days((millis / MILLIS_PER_DAY as u128) as u64);
+
+        match self.granularity {
+            Granularity::Day => Partition::of(self.granularity, year, month, day),
+            Granularity::Month => Partition::of(self.granularity, year, month, 1),
+            Granularity::Year => Partition::of(self.granularity, year, 1, 1),
+        }
+    }
+}
+
+impl Partition {
+    const fn of(granularity: Granularity, year: u64, month: u8, day: u8) -> Self {
+        Partition {
+            year,
+            month,
+            day,
+            granularity,
+        }
+    }
+
+    /// Returns the year.
+    pub const fn year(&self) -> u64 {
+        self.year
+    }
+
+    /// Returns the month, from 1 to 12.
+    pub const fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Returns the day of the month, from 1 to 31.
+    pub const fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Returns the granularity of the period.
+    pub const fn granularity(&self) -> Granularity {
+        self.granularity
+    }
+
+    /// Returns the first millisecond of the period, counted from the Unix epoch.
+    ///
+    /// The result is a `u128`, as periods of wide layouts counting in seconds can start beyond
+    /// the range of a `u64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::oat::Oat;
+    /// use oats::shard::{Granularity, TimePartitioner};
+    ///
+    /// let partition = TimePartitioner::of(Granularity::Day).partition(&Oat::of(1, 3, 1671800400_000));
+    ///
+    /// assert_eq!(partition.start_unix_millis(), 1671753600_000);
+    /// ```
+    pub const fn start_unix_millis(&self) -> u128 {
+        days_from_civil(self.year, self.month, self.day) as u128 * MILLIS_PER_DAY as u128
+    }
+}
+
+impl fmt::Display for Partition {
+    /// Formats the period as `YYYY-MM-DD`, `YYYY-MM` or `YYYY`, depending on its granularity.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.granularity {
+            Granularity::Day => write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day),
+            Granularity::Month => write!(f, "{:04}-{:02}", self.year, self.month),
+            Granularity::Year => write!(f, "{:04}", self.year),
+        }
+    }
+}
+
+/// Converts days since 1970-01-01 into year, month and day, after Howard Hinnant's `civil_from_days`.
+const fn civil_from_days(days: u64) -> (u64, u8, u8) {
+    // Shift the epoch to 0000-03-01, so leap days fall on the end of the year.
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month as u8, day as u8)
+}
+
+/// Converts year, month and day into days since 1970-01-01, after Howard Hinnant's `days_from_civil`.
+const fn days_from_civil(year: u64, month: u8, day: u8) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 } as u64;
+    let day_of_year = (153 * shifted_month + 2) / 5 + day as u64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
@@ -0,0 +1,254 @@
+use crate::oat::{Oat, ParseOatError};
+use alloc::vec::Vec;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use core::fmt;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// The number of bytes of the HMAC-SHA256 tag kept in a token, 96 bits.
+pub const TAG_LEN: usize = 12;
+
+/// The length of the string form of a `SignedOat`: the Oat, a dot, the key id and the tag.
+pub const ENCODED_LEN: usize = Oat::ENCODED_LEN + 1 + 2 + TAG_LEN / 3 * 4;
+
+/// An Oat carrying a truncated HMAC-SHA256 tag, for handing out Oats as capability tokens.
+///
+/// The string form appends a dot, the id of the signing key as two upper-case hex digits, and
+/// the first 12 bytes of the tag in URL-safe base64, 32 characters in total:
+///
+/// ```text
+/// X1AwCIGvFTGAA.01OGBBSb6I-WpoQkhW
+/// ^^^^^^^^^^^^^                     Oat
+///              ^                    separator
+///               ^^                  key id
+///                 ^^^^^^^^^^^^^^^^  tag
+/// ```
+///
+/// The tag covers the key id and the 9 bytes of `Oat::to_bytes`. Unlike the opaque tokens of
+/// `OatCipher`, a signed Oat can not be forged without the key, but anyone can read the Oat.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct SignedOat {
+    oat: Oat,
+    key_id: u8,
+    tag: [u8; TAG_LEN],
+}
+
+/// Signs Oats with the current key and verifies them against all known keys.
+///
+/// Rotating keys works by creating a signer with the new key as current key and the old ones
+/// added with `with_key`. Tokens signed with an old key keep verifying until it is dropped, and
+/// `SignedOat::key_id` tells which tokens should be reissued.
+///
+/// # Examples
+///
+/// ```
+/// use oats::oat::Oat;
+/// use oats::signed::OatSigner;
+///
+/// let old = OatSigner::of(1, b"old secret");
+/// let token = old.sign(&Oat::of(1, 3, 1671800400_000)).to_string();
+///
+/// let rotated = OatSigner::of(2, b"new secret").with_key(1, b"old secret");
+/// let signed = rotated.verify(&token).unwrap();
+///
+/// assert_eq!(signed.oat(), Oat::of(1, 3, 1671800400_000));
+/// assert_eq!(signed.key_id(), 1);
+/// assert_eq!(rotated.sign(&signed.oat()).key_id(), 2);
+/// ```
+#[derive(Clone)]
+pub struct OatSigner {
+    current: u8,
+    keys: Vec<(u8, Hmac<Sha256>)>,
+}
+
+/// The reasons a token is rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    /// The token does not have the length of a signed Oat.
+    InvalidLength(usize),
+    /// The byte at the given index is not valid at its position.
+    InvalidCharacter(usize),
+    /// The Oat in front of the tag is invalid.
+    InvalidOat(ParseOatError),
+    /// The token was signed with a key the signer does not know.
+    UnknownKey(u8),
+    /// The tag does not match the Oat, the token was tampered with.
+    InvalidTag,
+}
+
+impl SignedOat {
+    /// Returns the signed Oat.
+    pub const fn oat(&self) -> Oat {
+        self.oat
+    }
+
+    /// Returns the id of the key the Oat was signed with.
+    pub const fn key_id(&self) -> u8 {
+        self.key_id
+    }
+}
+
+impl OatSigner {
+    /// Creates a signer that signs with, and accepts, the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_id` - The id written into every token, identifying the key.
+    /// * `key` - The secret key, ideally 32 random bytes.
+    pub fn of(key_id: u8, key: &[u8]) -> Self {
+        OatSigner {
+            current: key_id,
+            keys: Vec::new(),
+        }
+        .with_key(key_id, key)
+    }
+
+    /// Returns a copy of the signer additionally accepting tokens signed with the given key.
+    ///
+    /// A key added under an id that is already known replaces the previous one.
+    pub fn with_key(mut self, key_id: u8, key: &[u8]) -> Self {
+        let mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length.");
+
+        self.keys.retain(|(id, _)| *id != key_id);
+        self.keys.push((key_id, mac));
+        self
+    }
+
+    /// Signs the Oat with the current key.
+    pub fn sign(&self, oat: &Oat) -> SignedOat {
+        let mac = self
+            .mac(self.current, oat)
+            .expect("The current key is known.");
+
+        let mut tag = [0; TAG_LEN];
+        tag.copy_from_slice(&mac.finalize().into_bytes()[..TAG_LEN]);
+
+        SignedOat {
+            oat: *oat,
+            key_id: self.current,
+            tag,
+        }
+    }
+
+    /// Parses a token and checks its tag in constant time.
+    ///
+    /// # Returns
+    ///
+    /// The signed Oat, or a `SignatureError` if the token is malformed, was signed with an
+    /// unknown key, or its tag does not match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::oat::Oat;
+    /// use oats::signed::{OatSigner, SignatureError};
+    ///
+    /// let signer = OatSigner::of(1, b"secret");
+    /// let token = signer.sign(&Oat::of(1, 3, 1671800400_000)).to_string();
+    /// let forged = token.replacen("X1", "X2", 1);
+    ///
+    /// assert_eq!(signer.verify(&forged), Err(SignatureError::InvalidTag));
+    /// ```
+    pub fn verify(&self, token: &str) -> Result<SignedOat, SignatureError> {
+        let bytes = token.as_bytes();
+        if bytes.len() != ENCODED_LEN {
+            return Err(SignatureError::InvalidLength(bytes.len()));
+        }
+
+        let oat =
+            Oat::from_ascii(&bytes[..Oat::ENCODED_LEN]).map_err(SignatureError::InvalidOat)?;
+        if bytes[Oat::ENCODED_LEN] != b'.' {
+            return Err(SignatureError::InvalidCharacter(Oat::ENCODED_LEN));
+        }
+        let key_id = parse_key_id(&bytes[Oat::ENCODED_LEN + 1..Oat::ENCODED_LEN + 3])?;
+
+        let mut tag = [0; TAG_LEN];
+        URL_SAFE_NO_PAD
+            .decode_slice(&bytes[Oat::ENCODED_LEN + 3..], &mut tag)
+            .map_err(|_| SignatureError::InvalidTag)?;
+
+        self.mac(key_id, &oat)
+            .ok_or(SignatureError::UnknownKey(key_id))?
+            .verify_truncated_left(&tag)
+            .map_err(|_| SignatureError::InvalidTag)?;
+
+        Ok(SignedOat { oat, key_id, tag })
+    }
+
+    /// Returns the MAC of the given key, fed with the key id and the Oat.
+    fn mac(&self, key_id: u8, oat: &Oat) -> Option<Hmac<Sha256>> {
+        let (_, mac) = self.keys.iter().find(|(id, _)| *id == key_id)?;
+
+        let mut mac = mac.clone();
+        mac.update(&[key_id]);
+        mac.update(&oat.to_bytes());
+        Some(mac)
+    }
+}
+
+/// Parses the two upper-case hex digits of the key id.
+fn parse_key_id(digits: &[u8]) -> Result<u8, SignatureError> {
+    let mut key_id = 0;
+    for (offset, &digit) in digits.iter().enumerate() {
+        let value = match digit {
+            b'0'..=b'9' => digit - b'0',
+            b'A'..=b'F' => digit - b'A' + 10,
+            _ => {
+                return Err(SignatureError::InvalidCharacter(
+                    Oat::ENCODED_LEN + 1 + offset,
+                ))
+            }
+        };
+        key_id = key_id << 4 | value;
+    }
+    Ok(key_id)
+}
+
+impl fmt::Display for SignedOat {
+    /// Formats the signed Oat as its 32-character token.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tag = [0; TAG_LEN / 3 * 4];
+        URL_SAFE_NO_PAD
+            .encode_slice(self.tag, &mut tag)
+            .expect("The tag fits into the buffer.");
+        let tag = core::str::from_utf8(&tag).expect("Base64 is ASCII.");
+
+        write!(f, "{}.{:02X}{tag}", self.oat, self.key_id)
+    }
+}
+
+/// Keeps the keys out of logs.
+impl fmt::Debug for OatSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key_ids: Vec<u8> = self.keys.iter().map(|(id, _)| *id).collect();
+
+        f.debug_struct("OatSigner")
+            .field("current", &self.current)
+            .field("key_ids", &key_ids)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureError::InvalidLength(len) => write!(f, "invalid signed Oat length: {len}"),
+            SignatureError::InvalidCharacter(index) => {
+                write!(f, "invalid character at index {index}")
+            }
+            SignatureError::InvalidOat(err) => write!(f, "invalid Oat: {err}"),
+            SignatureError::UnknownKey(key_id) => write!(f, "unknown signing key: {key_id}"),
+            SignatureError::InvalidTag => write!(f, "invalid signature"),
+        }
+    }
+}
+
+impl core::error::Error for SignatureError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            SignatureError::InvalidOat(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,398 @@
+use crate::epoch::EpochRegistry;
+use crate::layout::{Layout, LayoutError, TimeUnit};
+use crate::oat::Oat;
+use core::{fmt, ops::Deref};
+
+/// The version written into the header byte.
+const VERSION: u8 = 1;
+
+/// Set in the header byte if an epoch id byte follows the layout.
+const FLAG_EPOCH_ID: u8 = 0b0001;
+
+/// Set in the header byte if the payload is in big-endian, time-first order.
+const FLAG_BIG_ENDIAN: u8 = 0b0010;
+
+/// The length of the legacy encoding of `Oat::to_bytes`.
+const LEGACY_LEN: usize = 9;
+
+/// The length of the versioned encoding without an epoch id.
+const VERSIONED_LEN: usize = 13;
+
+/// The byte order of the 9 payload bytes of the versioned encoding.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum ByteOrder {
+    /// The node byte followed by the LUID in little-endian order, exactly like `Oat::to_bytes`.
+    #[default]
+    Little,
+    /// Timestamp, sequence number and node packed in big-endian order, so the bytes sort by time.
+    Big,
+}
+
+/// Describes the versioned encoding of an Oat: its layout, epoch and byte order.
+///
+/// The encoding starts with a header byte holding the version in the high nibble and flags in
+/// the low nibble, followed by three layout bytes, an optional epoch id byte and the 9 payload
+/// bytes, 13 or 14 bytes in total.
+///
+/// | Byte      | Content                                                        |
+/// |-----------|----------------------------------------------------------------|
+/// | 0         | `0x1` version, flags: `0b01` epoch id, `0b10` big-endian       |
+/// | 1         | node bits                                                      |
+/// | 2         | timestamp bits                                                 |
+/// | 3         | sequence bits, time unit in bits 5 and 6                       |
+/// | 4         | epoch id, if flagged                                           |
+/// | 4/5 - end | payload                                                        |
+///
+/// # Examples
+///
+/// ```
+/// use oats::layout::Layout;
+/// use oats::oat::Oat;
+/// use oats::wire::{ByteOrder, WireHeader};
+///
+/// let header = WireHeader::of(Layout::STANDARD)
+///     .with_epoch_id(7)
+///     .with_order(ByteOrder::Big);
+///
+/// let oat = Oat::of(1, 3, 1671800400_000);
+/// let bytes = oat.to_bytes_v(&header);
+///
+/// assert_eq!(bytes.len(), 14);
+/// assert_eq!(Oat::from_bytes_v(&bytes), Ok((oat, header)));
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct WireHeader {
+    layout: Layout,
+    epoch_id: Option<u8>,
+    order: ByteOrder,
+}
+
+/// The versioned encoding of an Oat, kept on the stack.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct WireBytes {
+    bytes: [u8; VERSIONED_LEN + 1],
+    len: u8,
+}
+
+/// The reasons decoding an Oat from bytes can fail.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum WireError {
+    /// The input is neither 9 bytes long nor as long as its header announces.
+    InvalidLength(usize),
+    /// The header byte announces a version this crate does not know.
+    UnsupportedVersion(u8),
+    /// The header byte has flags set this crate does not know.
+    UnsupportedFlags(u8),
+    /// The layout bytes do not describe a valid layout.
+    InvalidLayout(LayoutError),
+    /// The layout bytes carry an unknown time unit code.
+    InvalidUnit(u8),
+    /// The payload has bits set outside the fields of the layout.
+    NotInLayout,
+    /// The epoch id is not registered.
+    UnknownEpoch(u8),
+}
+
+impl WireHeader {
+    /// Creates a header for Oats of the given layout, without epoch id, in little-endian order.
+    pub const fn of(layout: Layout) -> Self {
+        WireHeader {
+            layout,
+            epoch_id: None,
+            order: ByteOrder::Little,
+        }
+    }
+
+    /// Returns a copy of the header carrying the given epoch id.
+    pub const fn with_epoch_id(self, epoch_id: u8) -> Self {
+        WireHeader {
+            epoch_id: Some(epoch_id),
+            ..self
+        }
+    }
+
+    /// Returns a copy of the header using the given byte order for the payload.
+    pub const fn with_order(self, order: ByteOrder) -> Self {
+        WireHeader { order, ..self }
+    }
+
+    /// Returns the layout of the encoded Oat.
+    pub const fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Returns the id of the epoch the timestamp counts from, if any.
+    pub const fn epoch_id(&self) -> Option<u8> {
+        self.epoch_id
+    }
+
+    /// Returns the byte order of the payload.
+    pub const fn order(&self) -> ByteOrder {
+        self.order
+    }
+
+    /// Returns the number of bytes the header takes up in front of the payload.
+    const fn len(&self) -> usize {
+        match self.epoch_id {
+            Some(_) => 5,
+            None => 4,
+        }
+    }
+}
+
+impl Default for WireHeader {
+    fn default() -> Self {
+        WireHeader::of(Layout::STANDARD)
+    }
+}
+
+impl Deref for WireBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+}
+
+impl AsRef<[u8]> for WireBytes {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl Oat {
+    /// Encodes the Oat in the versioned binary format described by `header`.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The layout the Oat was generated with, its epoch id and the byte order.
+    ///
+    /// # Returns
+    ///
+    /// The 13 or 14 encoded bytes.
+    pub fn to_bytes_v(&self, header: &WireHeader) -> WireBytes {
+        let layout = &header.layout;
+        let mut bytes = [0; VERSIONED_LEN + 1];
+
+        let mut flags = 0;
+        if header.epoch_id.is_some() {
+            flags |= FLAG_EPOCH_ID;
+        }
+        if header.order == ByteOrder::Big {
+            flags |= FLAG_BIG_ENDIAN;
+        }
+
+        bytes[0] = VERSION << 4 | flags;
+        bytes[1] = layout.node_bits();
+        bytes[2] = layout.timestamp_bits();
+        bytes[3] = layout.seq_bits() | unit_code(layout.unit()) << 5;
+        if let Some(epoch_id) = header.epoch_id {
+            bytes[4] = epoch_id;
+        }
+
+        let payload = match header.order {
+            ByteOrder::Little => self.to_bytes(),
+            ByteOrder::Big => time_first(self, layout),
+        };
+        let start = header.len();
+        bytes[start..start + LEGACY_LEN].copy_from_slice(&payload);
+
+        WireBytes {
+            bytes,
+            len: (start + LEGACY_LEN) as u8,
+        }
+    }
+
+    /// Decodes an Oat from either the legacy 9-byte form of `to_bytes` or the versioned form of `to_bytes_v`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The encoded bytes.
+    ///
+    /// # Returns
+    ///
+    /// The Oat and the header it was encoded with, where legacy bytes report the default header,
+    /// or a `WireError` if the bytes can not be decoded. The payload must fit the layout of the
+    /// header in either byte order. The epoch id is returned as is; use `from_bytes_v_in` to
+    /// reject ids a registry does not know.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::oat::Oat;
+    /// use oats::wire::WireHeader;
+    ///
+    /// let oat = Oat::of(1, 3, 1671800400_000);
+    ///
+    /// assert_eq!(Oat::from_bytes_v(&oat.to_bytes()), Ok((oat, WireHeader::default())));
+    /// ```
+    pub fn from_bytes_v(bytes: &[u8]) -> Result<(Self, WireHeader), WireError> {
+        if bytes.len() == LEGACY_LEN {
+            let payload = bytes.try_into().unwrap();
+            return Ok((Oat::from_bytes(payload), WireHeader::default()));
+        }
+        if bytes.len() < VERSIONED_LEN {
+            return Err(WireError::InvalidLength(bytes.len()));
+        }
+
+        let version = bytes[0] >> 4;
+        if version != VERSION {
+            return Err(WireError::UnsupportedVersion(version));
+        }
+        let flags = bytes[0] & 0xf;
+        if flags & !(FLAG_EPOCH_ID | FLAG_BIG_ENDIAN) != 0 {
+            return Err(WireError::UnsupportedFlags(flags));
+        }
+
+        let unit = match unit_from_code(bytes[3] >> 5) {
+            Some(unit) => unit,
+            None => return Err(WireError::InvalidUnit(bytes[3] >> 5)),
+        };
+        let layout = Layout::try_new(bytes[1], bytes[2], bytes[3] & 0x1f, unit)
+            .map_err(WireError::InvalidLayout)?;
+
+        let mut header = WireHeader::of(layout);
+        if flags & FLAG_EPOCH_ID != 0 {
+            header = header.with_epoch_id(bytes[4]);
+        }
+        if flags & FLAG_BIG_ENDIAN != 0 {
+            header = header.with_order(ByteOrder::Big);
+        }
+
+        let start = header.len();
+        if bytes.len() != start + LEGACY_LEN {
+            return Err(WireError::InvalidLength(bytes.len()));
+        }
+        let payload: [u8; LEGACY_LEN] = bytes[start..].try_into().unwrap();
+
+        let oat = match header.order {
+            ByteOrder::Little => from_little(payload, &layout)?,
+            ByteOrder::Big => from_time_first(payload, &layout)?,
+        };
+        Ok((oat, header))
+    }
+
+    /// Decodes an Oat like `from_bytes_v`, but only accepts epoch ids registered in `registry`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oats::epoch::EpochRegistry;
+    /// use oats::oat::Oat;
+    /// use oats::wire::{WireError, WireHeader};
+    ///
+    /// let oat = Oat::of(1, 3, 1671800400_000);
+    /// let known = oat.to_bytes_v(&WireHeader::default().with_epoch_id(1));
+    /// let unknown = oat.to_bytes_v(&WireHeader::default().with_epoch_id(7));
+    ///
+    /// assert!(Oat::from_bytes_v_in(&known, &EpochRegistry::STANDARD).is_ok());
+    /// assert_eq!(
+    ///     Oat::from_bytes_v_in(&unknown, &EpochRegistry::STANDARD),
+    ///     Err(WireError::UnknownEpoch(7))
+    /// );
+    /// ```
+    pub fn from_bytes_v_in(
+        bytes: &[u8],
+        registry: &EpochRegistry,
+    ) -> Result<(Self, WireHeader), WireError> {
+        let (oat, header) = Oat::from_bytes_v(bytes)?;
+        match header.epoch_id {
+            Some(epoch_id) if registry.get(epoch_id).is_none() => {
+                Err(WireError::UnknownEpoch(epoch_id))
+            }
+            _ => Ok((oat, header)),
+        }
+    }
+}
+
+/// Reads the little-endian payload of `Oat::to_bytes`, checking that it fits `layout`.
+fn from_little(payload: [u8; LEGACY_LEN], layout: &Layout) -> Result<Oat, WireError> {
+    let oat = Oat::from_bytes(payload);
+    let fitted = Oat::with_layout(
+        layout,
+        oat.node_in(layout),
+        oat.seq_in(layout),
+        oat.timestamp_in(layout),
+    );
+
+    if fitted != oat {
+        return Err(WireError::NotInLayout);
+    }
+    Ok(oat)
+}
+
+/// Packs timestamp, sequence number and node, in this order, into 9 big-endian bytes.
+fn time_first(oat: &Oat, layout: &Layout) -> [u8; LEGACY_LEN] {
+    let node_bits = layout.node_bits() as u32;
+    let value = (oat.timestamp_in(layout) as u128) << (layout.seq_bits() as u32 + node_bits)
+        | (oat.seq_in(layout) as u128) << node_bits
+        | oat.node_in(layout) as u128;
+
+    value.to_be_bytes()[16 - LEGACY_LEN..].try_into().unwrap()
+}
+
+/// Reverses `time_first`.
+fn from_time_first(payload: [u8; LEGACY_LEN], layout: &Layout) -> Result<Oat, WireError> {
+    let mut bytes = [0; 16];
+    bytes[16 - LEGACY_LEN..].copy_from_slice(&payload);
+    let value = u128::from_be_bytes(bytes);
+
+    if value >> layout.total_bits() != 0 {
+        return Err(WireError::NotInLayout);
+    }
+
+    let node_bits = layout.node_bits() as u32;
+    let node = (value & layout.max_node() as u128) as u16;
+    let seq = (value >> node_bits) as u16 & layout.max_seq();
+    let timestamp = (value >> (node_bits + layout.seq_bits() as u32)) as u64;
+
+    Ok(Oat::with_layout(layout, node, seq, timestamp))
+}
+
+/// Returns the two-bit code of a time unit in the layout bytes.
+const fn unit_code(unit: TimeUnit) -> u8 {
+    match unit {
+        TimeUnit::Millis => 0,
+        TimeUnit::TenMillis => 1,
+        TimeUnit::HundredMillis => 2,
+        TimeUnit::Seconds => 3,
+    }
+}
+
+/// Reverses `unit_code`.
+const fn unit_from_code(code: u8) -> Option<TimeUnit> {
+    match code {
+        0 => Some(TimeUnit::Millis),
+        1 => Some(TimeUnit::TenMillis),
+        2 => Some(TimeUnit::HundredMillis),
+        3 => Some(TimeUnit::Seconds),
+        _ => None,
+    }
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WireError::InvalidLength(len) => write!(f, "invalid encoded Oat length: {len}"),
+            WireError::UnsupportedVersion(version) => {
+                write!(f, "unsupported wire format version: {version}")
+            }
+            WireError::UnsupportedFlags(flags) => {
+                write!(f, "unsupported wire format flags: {flags:#06b}")
+            }
+            WireError::InvalidLayout(err) => write!(f, "invalid layout: {err}"),
+            WireError::InvalidUnit(code) => write!(f, "invalid time unit code: {code}"),
+            WireError::NotInLayout => write!(f, "payload does not match the layout"),
+            WireError::UnknownEpoch(epoch_id) => write!(f, "unknown epoch id: {epoch_id}"),
+        }
+    }
+}
+
+impl core::error::Error for WireError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            WireError::InvalidLayout(err) => Some(err),
+            _ => None,
+        }
+    }
+}
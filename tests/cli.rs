@@ -0,0 +1,150 @@
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn oats(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_oats"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to start oats.");
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// Test that `generate` prints the requested number of Oats in each text format.
+#[test]
+fn test_generate_formats() {
+    for (format, len) in [("b64", 13), ("base32", 15), ("hex", 18)] {
+        let output = oats(
+            &[
+                "generate", "--node", "7", "--count", "5", "--format", format,
+            ],
+            "",
+        );
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(
+            lines.iter().all(|line| line.len() == len),
+            "{format}: {lines:?}"
+        );
+    }
+
+    let output = oats(
+        &[
+            "generate", "--node", "7", "--count", "3", "--format", "bytes",
+        ],
+        "",
+    );
+    assert_eq!(output.stdout.len(), 27);
+    assert!(output.stdout.chunks(9).all(|oat| oat[0] == 7));
+}
+
+/// Test that `decode` accepts every output format of `generate`.
+#[test]
+fn test_decode_formats() {
+    for id in ["X1AwCIGvFTGAA", "AEBQBCA26FJRQAA", "010300881af1531800"] {
+        let output = oats(&["decode", id], "");
+        assert!(output.status.success(), "{id}");
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("node:      1"));
+        assert!(stdout.contains("region:    AN (Nauru)"));
+        assert!(stdout.contains("timestamp: 2022-12-23T13:00:00.000Z"));
+        assert!(stdout.contains("seq:       3"));
+    }
+}
+
+/// Test that `decode` honours the given epoch.
+#[test]
+fn test_decode_with_epoch() {
+    let output = oats(&["decode", "X1AwCIGvFTGAA", "--epoch", "1000"], "");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("timestamp: 2022-12-23T13:00:01.000Z"));
+}
+
+/// Test that `decode` honours the given time unit.
+#[test]
+fn test_decode_with_unit() {
+    let output = oats(&["decode", "X1AwCIGvFTGAA", "--unit", "10ms"], "");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(
+        stdout.contains("timestamp: 2499-10-09T10:00:00.000Z"),
+        "{stdout}"
+    );
+}
+
+/// Test that `decode` does not panic on timestamps beyond what can be formatted.
+#[test]
+fn test_decode_out_of_range() {
+    let output = oats(&["decode", "01ffffffffffffffff", "--unit", "s"], "");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("timestamp: -\n"), "{stdout}");
+}
+
+/// Test that `generate` refuses to count from an epoch in the future.
+#[test]
+fn test_generate_future_epoch() {
+    let output = oats(
+        &["generate", "--node", "1", "--epoch", "2999-01-01T00:00:00Z"],
+        "",
+    );
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("error: invalid epoch:"));
+}
+
+/// Test that `decode` rejects malformed input instead of panicking.
+#[test]
+fn test_decode_invalid() {
+    for id in ["", "X", "XÄAwCIGvFTGA", "not an oat at all"] {
+        let output = oats(&["decode", id], "");
+
+        assert_eq!(output.status.code(), Some(1), "{id}");
+        assert!(String::from_utf8(output.stderr)
+            .unwrap()
+            .starts_with("error:"));
+    }
+}
+
+/// Test that `inspect` prints one JSON object per input line.
+#[test]
+fn test_inspect() {
+    let output = oats(
+        &["inspect"],
+        "X1AwCIGvFTGAA\n\n  010300881af1531800  \nbogus\n",
+    );
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+
+    assert_eq!(lines.len(), 3);
+    assert_eq!(
+        lines[0],
+        r#"{"id":"X1AwCIGvFTGAA","node":1,"region":"AN","seq":3,"timestamp":"2022-12-23T13:00:00.000Z"}"#
+    );
+    assert_eq!(
+        lines[1],
+        r#"{"id":"010300881af1531800","node":1,"region":"AN","seq":3,"timestamp":"2022-12-23T13:00:00.000Z"}"#
+    );
+    assert!(lines[2].starts_with(r#"{"error":"#));
+}
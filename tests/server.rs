@@ -0,0 +1,186 @@
+#![cfg(feature = "server")]
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::process::{self, Child, Command, Stdio};
+
+use serde_json::Value;
+
+/// A running `oats-server` process, killed when dropped.
+struct TestServer {
+    child: Child,
+    address: SocketAddr,
+}
+
+impl TestServer {
+    fn start(name: &str, config: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("oats-server-{}-{name}.toml", process::id()));
+        fs::write(&path, config).unwrap();
+
+        let mut child = Command::new(env!("CARGO_BIN_EXE_oats-server"))
+            .arg(&path)
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to start oats-server.");
+
+        let mut line = String::new();
+        BufReader::new(child.stdout.as_mut().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let address = line
+            .trim()
+            .strip_prefix("listening on ")
+            .expect("Server did not report its address.")
+            .parse()
+            .unwrap();
+
+        TestServer { child, address }
+    }
+
+    fn get(&self, path: &str) -> (u16, Value) {
+        let (status, body) = self.get_text(path);
+        (status, serde_json::from_str(&body).unwrap())
+    }
+
+    fn get_text(&self, path: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(self.address).unwrap();
+        write!(
+            stream,
+            "GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, body.to_string())
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+const CONFIG: &str = r#"
+address = "127.0.0.1:0"
+node = 7
+behavior = "realtime"
+epoch = 1000
+max_count = 50
+workers = 2
+"#;
+
+/// Test that `/id` hands out a fresh id for the configured node.
+#[test]
+fn test_id() {
+    let server = TestServer::start("id", CONFIG);
+
+    let (status, body) = server.get("/id");
+    assert_eq!(status, 200);
+
+    let id = body["id"].as_str().unwrap();
+    assert_eq!(id.len(), 13);
+    assert!(id.starts_with("X7"));
+    assert_ne!(server.get("/id").1["id"], body["id"]);
+}
+
+/// Test that `/ids` honours and validates the requested count.
+#[test]
+fn test_ids() {
+    let server = TestServer::start("ids", CONFIG);
+
+    let (status, body) = server.get("/ids?count=50");
+    assert_eq!(status, 200);
+    let ids = body["ids"].as_array().unwrap();
+    assert_eq!(ids.len(), 50);
+    assert!(ids.windows(2).all(|pair| pair[0] != pair[1]));
+
+    assert_eq!(server.get("/ids").1["ids"].as_array().unwrap().len(), 1);
+    assert_eq!(server.get("/ids?count=51").0, 400);
+    assert_eq!(server.get("/ids?count=0").0, 400);
+    assert_eq!(server.get("/ids?count=many").0, 400);
+}
+
+/// Test that `/metrics` counts the ids handed out in the Prometheus text format.
+#[test]
+fn test_metrics() {
+    let server = TestServer::start("metrics", CONFIG);
+    server.get("/ids?count=5");
+
+    let (status, body) = server.get_text("/metrics");
+    assert_eq!(status, 200);
+    assert!(body.contains("# TYPE oats_ids_issued_total counter\n"));
+    assert!(body.contains("oats_ids_issued_total{node=\"7\",epoch=\"1000\"} 5\n"));
+    assert!(body.contains("oats_wait_seconds_bucket{node=\"7\",epoch=\"1000\",le=\"+Inf\"} "));
+}
+
+/// Test that `/decode/{id}` reports the fields relative to the configured epoch.
+#[test]
+fn test_decode() {
+    let server = TestServer::start("decode", CONFIG);
+
+    let (status, body) = server.get("/decode/X1AwCIGvFTGAA");
+    assert_eq!(status, 200);
+    assert_eq!(body["node"], 1);
+    assert_eq!(body["seq"], 3);
+    assert_eq!(body["timestamp"], 1_671_800_400_000u64);
+    assert_eq!(body["time"], "2022-12-23T13:00:01.000Z");
+
+    assert_eq!(server.get("/decode/X1").0, 400);
+    assert_eq!(server.get("/decode/X1AwCIGvFT!AA").0, 400);
+    assert_eq!(server.get("/unknown").0, 404);
+}
+
+/// Test that the server exits cleanly on SIGTERM.
+#[cfg(unix)]
+#[test]
+fn test_graceful_shutdown() {
+    let mut server = TestServer::start("shutdown", CONFIG);
+    assert_eq!(server.get("/id").0, 200);
+
+    let killed = Command::new("kill")
+        .args(["-TERM", &server.child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(killed.success());
+
+    assert!(server.child.wait().unwrap().success());
+}
+
+/// Test that an invalid config is rejected at startup.
+#[test]
+fn test_invalid_config() {
+    let path = std::env::temp_dir().join(format!("oats-server-{}-invalid.toml", process::id()));
+    fs::write(&path, "node = 300\n").unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_oats-server"))
+        .arg(&path)
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+
+    assert!(!status.success());
+}
+
+/// Test that an epoch in the future is rejected at startup.
+#[test]
+fn test_future_epoch() {
+    let path = std::env::temp_dir().join(format!("oats-server-{}-future.toml", process::id()));
+    fs::write(&path, "node = 1\nepoch = 32503680000000\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_oats-server"))
+        .arg(&path)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("lies in the future"));
+}